@@ -1,8 +1,14 @@
+use crate::format::{AlertView, Formatter, Link};
+use crate::latency::Timings;
+use crate::template::Markup;
 use alloy::primitives::{Address, TxHash, U256};
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TargetTransaction {
     pub tx_hash: TxHash,
     pub from: Address,
@@ -15,81 +21,174 @@ pub struct TargetTransaction {
     #[allow(dead_code)]
     pub deadline: U256,
     pub detected_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    #[serde(skip)]
+    pub timings: Timings,
 }
 
 #[async_trait]
 pub trait SniperAction: Send + Sync {
+    async fn start(&self) -> Result<()> {
+        Ok(())
+    }
+
     async fn execute(&self, tx: &TargetTransaction) -> Result<()>;
+
+    fn name(&self) -> &'static str {
+        "action"
+    }
+
+    async fn flush(&self, _timeout: Duration) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub const DEFAULT_CONSOLE_TEMPLATE: &str = "HIT! Hash: {{tx | full | yellow}} | Status: {{status}}{{#if block}} (block {{block}}){{/if}} | Value: {{value | green}} ETH | Method: {{method | cyan}} | To: {{to | full | magenta}} | Path Len: {{#if path}}{{path | len | blue}}{{else}}N/A{{/if}}";
+
+struct Ansi;
+
+impl Markup for Ansi {
+    fn escape(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn code(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn link(&self, link: &Link) -> String {
+        link.full.clone()
+    }
+
+    fn color(&self, color: &str, text: String) -> String {
+        let code = match color {
+            "red" => "31",
+            "green" => "32",
+            "yellow" => "33",
+            "blue" => "34",
+            "magenta" => "35",
+            "cyan" => "36",
+            "bold" => "1",
+            _ => return text,
+        };
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    }
 }
 
-pub struct ConsoleLogger;
+fn link_lines(view: &AlertView) -> Vec<String> {
+    let links = [("tx", &view.tx), ("from", &view.from), ("to", &view.to)]
+        .into_iter()
+        .chain(view.path.iter().map(|token| ("path", token)));
+
+    links
+        .filter_map(|(label, link)| link.url.as_ref().map(|url| format!("  -> {}: {}", label, url)))
+        .collect()
+}
+
+pub struct ConsoleLogger {
+    formatter: Formatter,
+}
+
+impl Default for ConsoleLogger {
+    fn default() -> Self {
+        Self::new(Formatter::with_default(DEFAULT_CONSOLE_TEMPLATE))
+    }
+}
 
 impl ConsoleLogger {
-    pub fn new() -> Self {
-        Self
+    pub fn new(formatter: Formatter) -> Self {
+        Self { formatter }
     }
 }
 
 #[async_trait]
 impl SniperAction for ConsoleLogger {
     async fn execute(&self, tx: &TargetTransaction) -> Result<()> {
-        let value_eth = format_wei_to_eth(tx.value);
+        let view = self.formatter.view(tx);
 
-        let path_display = if tx.path.is_empty() {
-            "N/A".to_string()
-        } else {
-            tx.path.len().to_string()
-        };
+        tracing::info!(target: "sniper", "{}", self.formatter.render(&view, &Ansi));
 
-        tracing::info!(
-            target: "sniper",
-            "HIT! Hash: {} | Value: {} ETH | Method: {} | To: {} | Path Len: {}",
-            format_args!("\x1b[33m{}\x1b[0m", tx.tx_hash),
-            format_args!("\x1b[32m{}\x1b[0m", value_eth),
-            format_args!("\x1b[36m{}\x1b[0m", tx.method),
-            format_args!("\x1b[35m{:?}\x1b[0m", tx.to),
-            format_args!("\x1b[34m{}\x1b[0m", path_display)
-        );
+        for line in link_lines(&view) {
+            tracing::info!(target: "sniper", "{}", line);
+        }
 
         Ok(())
     }
-}
 
-fn format_wei_to_eth(wei: U256) -> String {
-    let eth_divisor = U256::from(1_000_000_000_000_000_000u128);
-    let eth_whole = wei / eth_divisor;
-    let eth_fraction = wei % eth_divisor;
+    fn name(&self) -> &'static str {
+        "console"
+    }
+}
 
-    let fraction_scaled = eth_fraction * U256::from(10000u128) / eth_divisor;
+pub struct FanOut {
+    actions: Vec<Arc<dyn SniperAction>>,
+}
 
-    format!("{}.{:04}", eth_whole, fraction_scaled)
+impl FanOut {
+    pub fn new(actions: Vec<Arc<dyn SniperAction>>) -> Self {
+        Self { actions }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[async_trait]
+impl SniperAction for FanOut {
+    async fn start(&self) -> Result<()> {
+        for action in &self.actions {
+            action.start().await?;
+        }
 
-    #[test]
-    fn test_format_wei_to_eth() {
-        let one_eth = U256::from(1_000_000_000_000_000_000u128);
-        assert_eq!(format_wei_to_eth(one_eth), "1.0000");
+        Ok(())
+    }
+
+    async fn execute(&self, tx: &TargetTransaction) -> Result<()> {
+        let mut failed = Vec::new();
+        for action in &self.actions {
+            if let Err(e) = action.execute(tx).await {
+                tracing::error!("{} action failed: {}", action.name(), e);
+                failed.push(action.name());
+            }
+        }
+
+        if !failed.is_empty() {
+            anyhow::bail!("actions failed: {}", failed.join(", "));
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "fanout"
+    }
 
-        let half_eth = U256::from(500_000_000_000_000_000u128);
-        assert_eq!(format_wei_to_eth(half_eth), "0.5000");
+    async fn flush(&self, timeout: Duration) -> Result<()> {
+        let mut flushes = tokio::task::JoinSet::new();
+        for action in &self.actions {
+            let action = action.clone();
+            flushes.spawn(async move { (action.name(), action.flush(timeout).await) });
+        }
 
-        let ten_quarter_eth = U256::from(10_250_000_000_000_000_000u128);
-        assert_eq!(format_wei_to_eth(ten_quarter_eth), "10.2500");
+        while let Some(joined) = flushes.join_next().await {
+            if let Ok((name, Err(e))) = joined {
+                tracing::warn!("{} flush incomplete: {}", name, e);
+            }
+        }
 
-        let small_eth = U256::from(100_000_000_000_000u128);
-        assert_eq!(format_wei_to_eth(small_eth), "0.0001");
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[tokio::test]
     async fn test_console_logger_execute() {
-        let logger = ConsoleLogger::new();
+        let logger = ConsoleLogger::default();
 
         let tx = TargetTransaction {
             tx_hash: TxHash::default(),
+            from: Address::default(),
             value: U256::from(1_000_000_000_000_000_000u128),
             method: "swapExactETHForTokens".to_string(),
             amount_out_min: U256::from(1000u64),
@@ -105,9 +204,37 @@ mod tests {
                 .parse()
                 .unwrap(),
             deadline: U256::from(1700000000u64),
+            detected_at: 0,
+            block_number: None,
+            timings: Timings::default(),
         };
 
         let result = logger.execute(&tx).await;
         assert!(result.is_ok(), "ConsoleLogger should execute successfully");
+
+        let line = logger.formatter.render(&logger.formatter.view(&tx), &Ansi);
+        assert!(line.starts_with("HIT! Hash: \x1b[33m0x0000"));
+        assert!(line.contains("Value: \x1b[32m1.0000\x1b[0m ETH"));
+        assert!(line.ends_with("Path Len: \x1b[34m2\x1b[0m"));
+    }
+
+    #[test]
+    fn test_console_links_cover_every_address() {
+        let mut tx = crate::fixtures::sample_tx();
+        tx.path.push(Address::from([0x55; 20]));
+
+        let view = AlertView::new(&tx, &crate::chain::Explorer::for_chain(crate::chain::MAINNET));
+        assert_eq!(
+            link_lines(&view),
+            vec![
+                format!("  -> tx: https://etherscan.io/tx/0x{}", "11".repeat(32)),
+                format!("  -> from: https://etherscan.io/address/0x{}", "22".repeat(20)),
+                format!("  -> to: https://etherscan.io/address/0x{}", "44".repeat(20)),
+                format!("  -> path: https://etherscan.io/token/0x{}", "33".repeat(20)),
+                format!("  -> path: https://etherscan.io/token/0x{}", "55".repeat(20)),
+            ]
+        );
+
+        assert!(link_lines(&AlertView::new(&tx, &crate::chain::Explorer::default())).is_empty());
     }
 }
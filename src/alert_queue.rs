@@ -0,0 +1,571 @@
+use crate::action::TargetTransaction;
+use crate::metrics::{metrics, Gauge};
+use alloy::primitives::U256;
+use anyhow::{Context, Result};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::sync::Notify;
+
+pub const DEFAULT_CAPACITY: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropPolicy {
+    DropLowestValue,
+    DropNewest,
+}
+
+impl DropPolicy {
+    pub fn from_str_lossy(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "newest" | "drop-newest" => DropPolicy::DropNewest,
+            _ => DropPolicy::DropLowestValue,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    Queued,
+    Evicted,
+    Spilled,
+    Dropped,
+}
+
+#[derive(Default)]
+struct QueueState {
+    items: BTreeMap<u64, TargetTransaction>,
+    by_value: BTreeSet<(U256, Reverse<u64>)>,
+    seq: u64,
+    in_flight: usize,
+    spilled: usize,
+}
+
+impl QueueState {
+    fn insert(&mut self, tx: TargetTransaction) {
+        let seq = self.seq;
+        self.seq += 1;
+        self.by_value.insert((tx.value, Reverse(seq)));
+        self.items.insert(seq, tx);
+    }
+
+    fn remove(&mut self, seq: u64) -> Option<TargetTransaction> {
+        let tx = self.items.remove(&seq)?;
+        self.by_value.remove(&(tx.value, Reverse(seq)));
+        Some(tx)
+    }
+
+    fn lowest(&self) -> Option<(U256, u64)> {
+        self.by_value.first().map(|(value, Reverse(seq))| (*value, *seq))
+    }
+
+    fn next(&self, fifo: bool) -> Option<u64> {
+        if fifo {
+            self.items.keys().next().copied()
+        } else {
+            self.by_value.last().map(|(_, Reverse(seq))| *seq)
+        }
+    }
+
+    fn idle(&self) -> bool {
+        self.items.is_empty() && self.in_flight == 0 && self.spilled == 0
+    }
+}
+
+struct SpillFile {
+    path: PathBuf,
+    offset: u64,
+    pending: bool,
+}
+
+pub struct AlertQueue {
+    capacity: usize,
+    policy: DropPolicy,
+    fifo: bool,
+    spill: Option<tokio::sync::Mutex<SpillFile>>,
+    state: Mutex<QueueState>,
+    depth: Arc<Gauge>,
+    available: Notify,
+    idle: Notify,
+}
+
+impl AlertQueue {
+    pub fn new(capacity: usize, policy: DropPolicy, spill_path: Option<PathBuf>) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            policy,
+            fifo: false,
+            spill: spill_path.map(|path| {
+                tokio::sync::Mutex::new(SpillFile {
+                    path,
+                    offset: 0,
+                    pending: true,
+                })
+            }),
+            state: Mutex::new(QueueState::default()),
+            depth: Arc::default(),
+            available: Notify::new(),
+            idle: Notify::new(),
+        }
+    }
+
+    pub fn fifo(mut self) -> Self {
+        self.fifo = true;
+        self
+    }
+
+    pub fn with_depth(mut self, depth: Arc<Gauge>) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("DISCORD_QUEUE_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        let policy = std::env::var("DISCORD_DROP_POLICY")
+            .map(|v| DropPolicy::from_str_lossy(&v))
+            .unwrap_or(DropPolicy::DropLowestValue);
+        let spill_path = std::env::var("DISCORD_SPILL_PATH").ok().map(PathBuf::from);
+
+        Self::new(capacity, policy, spill_path)
+    }
+
+    pub async fn push(&self, tx: TargetTransaction) -> PushOutcome {
+        let (mut outcome, overflow) = self.insert(tx);
+
+        if let Some(overflow) = overflow {
+            match self.spill(std::slice::from_ref(&overflow)).await {
+                Ok(true) => {
+                    metrics().alerts_spilled.inc();
+                    if outcome == PushOutcome::Dropped {
+                        outcome = PushOutcome::Spilled;
+                    }
+                }
+                Ok(false) => {
+                    metrics().alerts_dropped.inc();
+                    tracing::warn!("alert queue full - dropping alert for tx {:?}", overflow.tx_hash);
+                }
+                Err(e) => {
+                    metrics().alerts_dropped.inc();
+                    tracing::error!("failed to spill alert for tx {:?}: {}", overflow.tx_hash, e);
+                }
+            }
+        }
+
+        outcome
+    }
+
+    fn insert(&self, tx: TargetTransaction) -> (PushOutcome, Option<TargetTransaction>) {
+        let mut state = self.state.lock().unwrap();
+
+        let mut outcome = PushOutcome::Queued;
+        let mut overflow = None;
+
+        if state.items.len() >= self.capacity {
+            let evict = match (self.policy, state.lowest()) {
+                (DropPolicy::DropLowestValue, Some((value, seq))) if value < tx.value => Some(seq),
+                _ => None,
+            };
+
+            match evict {
+                Some(seq) => {
+                    overflow = state.remove(seq);
+                    outcome = PushOutcome::Evicted;
+                }
+                None => outcome = PushOutcome::Dropped,
+            }
+        }
+
+        if outcome == PushOutcome::Dropped {
+            overflow = Some(tx);
+        } else {
+            state.insert(tx);
+        }
+        self.depth.set(state.items.len() as u64);
+        drop(state);
+
+        if outcome != PushOutcome::Dropped {
+            metrics().alerts_queued.inc();
+            self.available.notify_one();
+        }
+
+        (outcome, overflow)
+    }
+
+    pub async fn pop(&self) -> TargetTransaction {
+        loop {
+            if let Some(tx) = self.try_pop() {
+                return tx;
+            }
+
+            if let Err(e) = self.refill().await {
+                tracing::error!("failed to reload spilled alerts: {}", e);
+            }
+            if let Some(tx) = self.try_pop() {
+                return tx;
+            }
+
+            self.available.notified().await;
+        }
+    }
+
+    pub fn try_pop(&self) -> Option<TargetTransaction> {
+        let mut state = self.state.lock().unwrap();
+        let seq = state.next(self.fifo)?;
+        let tx = state.remove(seq)?;
+        state.in_flight += 1;
+        self.depth.set(state.items.len() as u64);
+        Some(tx)
+    }
+
+    pub fn done(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight = state.in_flight.saturating_sub(1);
+        if state.idle() {
+            self.idle.notify_waiters();
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.state.lock().unwrap().items.len()
+    }
+
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                let idle = self.idle.notified();
+                {
+                    if self.state.lock().unwrap().idle() {
+                        return;
+                    }
+                }
+                idle.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+
+    pub async fn spill_pending(&self) -> Result<usize> {
+        if self.spill.is_none() {
+            return Ok(0);
+        }
+
+        let pending: Vec<_> = {
+            let mut state = self.state.lock().unwrap();
+            let mut pending = Vec::with_capacity(state.items.len());
+            while let Some(tx) = state.next(self.fifo).and_then(|seq| state.remove(seq)) {
+                pending.push(tx);
+            }
+            self.depth.set(0);
+            pending
+        };
+
+        self.compact().await?;
+        self.spill(&pending).await?;
+        metrics().alerts_spilled.add(pending.len() as u64);
+
+        Ok(pending.len())
+    }
+
+    async fn spill(&self, txs: &[TargetTransaction]) -> Result<bool> {
+        let Some(spill) = &self.spill else {
+            return Ok(false);
+        };
+
+        let mut lines = String::new();
+        for tx in txs {
+            lines.push_str(&serde_json::to_string(tx)?);
+            lines.push('\n');
+        }
+
+        let mut spill = spill.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&spill.path)
+            .await
+            .with_context(|| format!("failed to open spill file {}", spill.path.display()))?;
+        file.write_all(lines.as_bytes()).await?;
+        file.flush().await?;
+        spill.pending = true;
+        self.state.lock().unwrap().spilled += txs.len();
+
+        Ok(true)
+    }
+
+    async fn compact(&self) -> Result<()> {
+        let Some(spill) = &self.spill else {
+            return Ok(());
+        };
+
+        let mut spill = spill.lock().await;
+        if spill.offset == 0 {
+            return Ok(());
+        }
+
+        let contents = tokio::fs::read(&spill.path).await?;
+        let rest = contents.get(spill.offset as usize..).unwrap_or_default();
+        tokio::fs::write(&spill.path, rest).await?;
+        spill.offset = 0;
+
+        Ok(())
+    }
+
+    async fn refill(&self) -> Result<()> {
+        let Some(spill) = &self.spill else {
+            return Ok(());
+        };
+
+        let mut spill = spill.lock().await;
+        if !spill.pending {
+            return Ok(());
+        }
+
+        let room = self.capacity.saturating_sub(self.depth());
+        if room == 0 {
+            return Ok(());
+        }
+
+        let file = match tokio::fs::File::open(&spill.path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                spill.pending = false;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(spill.offset)).await?;
+
+        let mut loaded = Vec::new();
+        let mut consumed = 0;
+        let mut line = String::new();
+        while loaded.len() < room {
+            line.clear();
+            let read = reader.read_line(&mut line).await?;
+            if read == 0 {
+                break;
+            }
+            spill.offset += read as u64;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+            consumed += 1;
+            match serde_json::from_str::<TargetTransaction>(&line) {
+                Ok(tx) => loaded.push(tx),
+                Err(e) => tracing::warn!("skipping unreadable spilled alert: {}", e),
+            }
+        }
+
+        let drained = reader.fill_buf().await?.is_empty();
+        if drained {
+            tokio::fs::File::create(&spill.path).await?;
+            spill.offset = 0;
+            spill.pending = false;
+        }
+        let path = spill.path.clone();
+        drop(spill);
+
+        let reloaded = loaded.len();
+        let idle = {
+            let mut state = self.state.lock().unwrap();
+            state.spilled = if drained { 0 } else { state.spilled.saturating_sub(consumed) };
+            for tx in loaded {
+                state.insert(tx);
+            }
+            self.depth.set(state.items.len() as u64);
+            state.idle()
+        };
+
+        if reloaded == 0 {
+            if idle {
+                self.idle.notify_waiters();
+            }
+            return Ok(());
+        }
+
+        self.available.notify_one();
+        tracing::info!("reloaded {} spilled alerts from {}", reloaded, path.display());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::target_tx;
+    use alloy::primitives::{TxHash, U256};
+
+    fn tx(id: u8, value: u64) -> TargetTransaction {
+        TargetTransaction {
+            value: U256::from(value),
+            ..target_tx(id)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pops_highest_value_first() {
+        let queue = AlertQueue::new(10, DropPolicy::DropLowestValue, None);
+        queue.push(tx(1, 5)).await;
+        queue.push(tx(2, 50)).await;
+        queue.push(tx(3, 5)).await;
+        queue.push(tx(4, 20)).await;
+
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([2; 32]));
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([4; 32]));
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([1; 32]), "equal values keep fifo order");
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([3; 32]));
+    }
+
+    #[tokio::test]
+    async fn test_drop_lowest_value_policy() {
+        let queue = AlertQueue::new(2, DropPolicy::DropLowestValue, None);
+        assert_eq!(queue.push(tx(1, 10)).await, PushOutcome::Queued);
+        assert_eq!(queue.push(tx(2, 30)).await, PushOutcome::Queued);
+        assert_eq!(queue.push(tx(3, 20)).await, PushOutcome::Evicted);
+        assert_eq!(queue.push(tx(4, 1)).await, PushOutcome::Dropped);
+        assert_eq!(queue.depth(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fifo_queue_keeps_arrival_order() {
+        let queue = AlertQueue::new(3, DropPolicy::DropLowestValue, None).fifo();
+        queue.push(tx(1, 5)).await;
+        queue.push(tx(2, 50)).await;
+        queue.push(tx(3, 20)).await;
+        assert_eq!(queue.push(tx(4, 30)).await, PushOutcome::Evicted, "overflow still evicts the lowest value");
+
+        for id in [2, 3, 4] {
+            assert_eq!(queue.pop().await.tx_hash, TxHash::from([id; 32]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest_policy() {
+        let queue = AlertQueue::new(1, DropPolicy::DropNewest, None);
+        assert_eq!(queue.push(tx(1, 10)).await, PushOutcome::Queued);
+        assert_eq!(queue.push(tx(2, 1000)).await, PushOutcome::Dropped);
+        assert_eq!(queue.depth(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_spill_and_reload() {
+        let path = std::env::temp_dir().join(format!("sniper-spill-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let queue = AlertQueue::new(1, DropPolicy::DropNewest, Some(path.clone()));
+        assert_eq!(queue.push(tx(1, 10)).await, PushOutcome::Queued);
+        assert_eq!(queue.push(tx(2, 20)).await, PushOutcome::Spilled);
+
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([1; 32]));
+        queue.done();
+
+        let reloaded = queue.pop().await;
+        assert_eq!(reloaded.tx_hash, TxHash::from([2; 32]));
+        assert_eq!(reloaded.value, U256::from(20u64));
+        queue.done();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_refill_reads_spill_file_incrementally() {
+        let path = std::env::temp_dir().join(format!("sniper-spill-offset-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let queue = AlertQueue::new(1, DropPolicy::DropNewest, Some(path.clone()));
+        queue.push(tx(1, 10)).await;
+        assert_eq!(queue.push(tx(2, 20)).await, PushOutcome::Spilled);
+        assert_eq!(queue.push(tx(3, 30)).await, PushOutcome::Spilled);
+        let spilled = std::fs::read_to_string(&path).unwrap();
+
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([1; 32]));
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([2; 32]));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), spilled, "partial refill leaves the file alone");
+
+        assert_eq!(queue.spill_pending().await.unwrap(), 0);
+        let restarted = AlertQueue::new(1, DropPolicy::DropNewest, Some(path.clone()));
+        assert_eq!(restarted.pop().await.tx_hash, TxHash::from([3; 32]), "consumed alerts are not replayed");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_spill_pending_on_shutdown() {
+        let path = std::env::temp_dir().join(format!("sniper-spill-pending-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let queue = AlertQueue::new(10, DropPolicy::DropLowestValue, Some(path.clone()));
+        queue.push(tx(1, 10)).await;
+        queue.push(tx(2, 30)).await;
+        assert_eq!(queue.spill_pending().await.unwrap(), 2);
+        assert_eq!(queue.depth(), 0);
+
+        let restarted = AlertQueue::new(10, DropPolicy::DropLowestValue, Some(path.clone()));
+        assert_eq!(restarted.pop().await.tx_hash, TxHash::from([2; 32]));
+        assert_eq!(restarted.pop().await.tx_hash, TxHash::from([1; 32]));
+
+        assert_eq!(AlertQueue::new(10, DropPolicy::DropLowestValue, None).spill_pending().await.unwrap(), 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_queues_report_their_own_depth() {
+        let first = AlertQueue::new(10, DropPolicy::DropLowestValue, None)
+            .with_depth(metrics().alert_queue_depth.with_label("test-first"));
+        let second = AlertQueue::new(10, DropPolicy::DropLowestValue, None)
+            .with_depth(metrics().alert_queue_depth.with_label("test-second"));
+
+        first.push(tx(1, 10)).await;
+        first.push(tx(2, 10)).await;
+        second.push(tx(3, 10)).await;
+        second.pop().await;
+
+        assert_eq!(metrics().alert_queue_depth.get("test-first"), 2);
+        assert_eq!(metrics().alert_queue_depth.get("test-second"), 0);
+        assert!(metrics().render().contains("sniper_alert_queue_depth{queue=\"test-first\"} 2"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle() {
+        let queue = AlertQueue::new(10, DropPolicy::DropLowestValue, None);
+        assert!(queue.wait_idle(Duration::from_millis(10)).await);
+
+        queue.push(tx(1, 10)).await;
+        assert!(!queue.wait_idle(Duration::from_millis(10)).await);
+
+        queue.pop().await;
+        queue.done();
+        assert!(queue.wait_idle(Duration::from_millis(10)).await);
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle_counts_spilled_alerts() {
+        let path = std::env::temp_dir().join(format!("sniper-spill-idle-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let queue = AlertQueue::new(1, DropPolicy::DropNewest, Some(path.clone()));
+        queue.push(tx(1, 10)).await;
+        assert_eq!(queue.push(tx(2, 20)).await, PushOutcome::Spilled);
+
+        queue.try_pop().unwrap();
+        queue.done();
+        assert_eq!(queue.depth(), 0);
+        assert!(!queue.wait_idle(Duration::from_millis(10)).await, "an alert is still on disk");
+
+        assert_eq!(queue.pop().await.tx_hash, TxHash::from([2; 32]));
+        queue.done();
+        assert!(queue.wait_idle(Duration::from_millis(10)).await);
+        let _ = std::fs::remove_file(&path);
+    }
+}
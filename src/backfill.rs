@@ -0,0 +1,276 @@
+use crate::decoder::Decoder;
+use crate::ingestor::{IngestError, PendingTx, SniperProvider};
+use crate::latency::Timings;
+use crate::metrics::metrics;
+use crate::overflow;
+use crate::shutdown::Shutdown;
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::Provider;
+use alloy::rpc::types::{Block, BlockTransactionsKind};
+use anyhow::{Context, Result};
+use futures_util::stream::{self, StreamExt};
+
+pub const DEFAULT_BACKFILL_CONCURRENCY: usize = 4;
+
+const MAX_BLOCK_ATTEMPTS: u32 = 5;
+const PROGRESS_EVERY_BLOCKS: u64 = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillConfig {
+    pub from: u64,
+    pub to: Option<u64>,
+    pub concurrency: usize,
+}
+
+impl BackfillConfig {
+    pub fn new(from: u64, to: Option<u64>) -> Self {
+        Self {
+            from,
+            to,
+            concurrency: DEFAULT_BACKFILL_CONCURRENCY,
+        }
+    }
+
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(from) = std::env::var("BACKFILL_FROM").ok().filter(|v| !v.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let from = from
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid BACKFILL_FROM: {}", from))?;
+
+        let to = match std::env::var("BACKFILL_TO")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty() && v != "latest")
+        {
+            Some(to) => Some(to.parse().with_context(|| format!("invalid BACKFILL_TO: {}", to))?),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            from,
+            to,
+            concurrency: std::env::var("BACKFILL_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_BACKFILL_CONCURRENCY)
+                .max(1),
+        }))
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BackfillStats {
+    pub blocks: u64,
+    pub transactions: u64,
+    pub missing: u64,
+}
+
+pub async fn backfill(
+    provider: &SniperProvider,
+    config: &BackfillConfig,
+    tx_channel: &overflow::Sender<PendingTx>,
+    decoder: &dyn Decoder,
+    shutdown: &Shutdown,
+) -> Result<BackfillStats> {
+    let to = match config.to {
+        Some(to) => to,
+        None => provider
+            .get_block_number()
+            .await
+            .context("failed to fetch the latest block number")?,
+    };
+    anyhow::ensure!(config.from <= to, "backfill range is empty: block {} is after {}", config.from, to);
+
+    let total = to - config.from + 1;
+    tracing::info!("backfilling blocks {} to {} ({} blocks)", config.from, to, total);
+
+    let mut blocks = stream::iter(config.from..=to)
+        .map(|number| fetch_block(provider, number))
+        .buffered(config.concurrency.max(1));
+    let mut stats = BackfillStats::default();
+
+    loop {
+        let fetched = tokio::select! {
+            biased;
+            _ = shutdown.wait() => break,
+            fetched = blocks.next() => fetched,
+        };
+        let Some((number, block)) = fetched else {
+            break;
+        };
+
+        let Some(block) = block? else {
+            stats.missing += 1;
+            metrics().backfill_missing.inc();
+            tracing::warn!("block {} not found - skipping", number);
+            continue;
+        };
+
+        for tx in block.transactions.into_transactions() {
+            let mut timings = Timings::start();
+            timings.fetched_at = Some(timings.received_at);
+            metrics().hashes_received.inc();
+            metrics().fetch_ok.inc();
+            metrics().backfill_transactions.inc();
+
+            let mut pending = PendingTx::new(tx, timings);
+            let value = pending.priority(decoder, tx_channel.ranks_by_value());
+            let outcome = tokio::select! {
+                biased;
+                _ = shutdown.wait() => return Ok(stats),
+                outcome = tx_channel.send_wait(pending, value) => outcome,
+            };
+            if outcome == overflow::SendOutcome::Closed {
+                return Ok(stats);
+            }
+            stats.transactions += 1;
+        }
+
+        stats.blocks += 1;
+        metrics().backfill_blocks.inc();
+        metrics().backfill_head.set(number);
+
+        if stats.blocks.is_multiple_of(PROGRESS_EVERY_BLOCKS) {
+            tracing::info!("backfilled {} of {} blocks (at block {})", stats.blocks, total, number);
+        }
+    }
+
+    Ok(stats)
+}
+
+async fn fetch_block(provider: &SniperProvider, number: u64) -> (u64, Result<Option<Block>>) {
+    let mut attempt = 0u32;
+
+    loop {
+        let fetched = provider
+            .get_block_by_number(BlockNumberOrTag::Number(number), BlockTransactionsKind::Full)
+            .await;
+
+        let err = match fetched {
+            Ok(block) => return (number, Ok(block)),
+            Err(e) => IngestError::from(e),
+        };
+
+        attempt += 1;
+        if attempt >= MAX_BLOCK_ATTEMPTS {
+            return (
+                number,
+                Err(anyhow::anyhow!("failed to fetch block {} after {} attempts: {}", number, attempt, err)),
+            );
+        }
+
+        let delay = err.retry_delay(attempt - 1);
+        tracing::warn!("block {}: {} - retrying in {:?}", number, err, delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::SelectorDecoder;
+    use crate::ingestor::establish_connection;
+    use crate::fixtures::rpc_tx;
+    use crate::mock_node::MockNode;
+    use crate::overflow::{OverflowPolicy, QueueConfig};
+    use alloy::consensus::Transaction;
+
+    fn queue() -> (overflow::Sender<PendingTx>, overflow::Receiver<PendingTx>) {
+        queue_with_capacity(100)
+    }
+
+    fn queue_with_capacity(capacity: usize) -> (overflow::Sender<PendingTx>, overflow::Receiver<PendingTx>) {
+        overflow::channel(QueueConfig {
+            capacity,
+            policy: OverflowPolicy::DropNewest,
+            priority: false,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_backfill_pushes_mined_transactions_in_block_order() {
+        let node = MockNode::start().await;
+        let first = node.add_block(10, vec![rpc_tx(1, 5), rpc_tx(2, 6)]);
+        let second = node.add_block(12, vec![rpc_tx(3, 7)]);
+        let conn = establish_connection(&node.url).await.unwrap();
+
+        let (sender, receiver) = queue();
+        let config = BackfillConfig {
+            concurrency: 2,
+            ..BackfillConfig::new(10, Some(12))
+        };
+        let stats = backfill(&conn.provider, &config, &sender, &SelectorDecoder, &Shutdown::new())
+            .await
+            .unwrap();
+        assert_eq!(stats, BackfillStats { blocks: 2, transactions: 3, missing: 1 });
+
+        for (hash, block) in [(first[0], 10), (first[1], 10), (second[0], 12)] {
+            let pending = receiver.recv().await.unwrap();
+            assert_eq!(*pending.tx.inner.tx_hash(), hash);
+            assert_eq!(pending.tx.block_number, Some(block));
+        }
+        assert_eq!(node.calls("eth_getBlockByNumber"), 3);
+        assert_eq!(node.calls("eth_blockNumber"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_runs_up_to_latest_block() {
+        let node = MockNode::start().await;
+        node.add_block(5, vec![rpc_tx(4, 1)]);
+        node.add_block(6, vec![rpc_tx(5, 1), rpc_tx(6, 2)]);
+        let conn = establish_connection(&node.url).await.unwrap();
+
+        let (sender, receiver) = queue();
+        let stats = backfill(&conn.provider, &BackfillConfig::new(5, None), &sender, &SelectorDecoder, &Shutdown::new())
+            .await
+            .unwrap();
+        assert_eq!(stats.blocks, 2);
+        assert_eq!(stats.transactions, 3);
+        assert_eq!(receiver.recv().await.unwrap().tx.inner.value(), alloy::primitives::U256::from(1));
+
+        let empty = backfill(&conn.provider, &BackfillConfig::new(7, None), &sender, &SelectorDecoder, &Shutdown::new()).await;
+        assert!(empty.is_err(), "a range starting after the latest block is rejected");
+    }
+
+    #[tokio::test]
+    async fn test_backfill_waits_for_space_instead_of_dropping() {
+        let node = MockNode::start().await;
+        let mined = node.add_block(3, (1..=6).map(|n| rpc_tx(n, 1)).collect());
+        let conn = establish_connection(&node.url).await.unwrap();
+
+        let (sender, receiver) = queue_with_capacity(1);
+        let backfilling = tokio::spawn(async move {
+            let stats = backfill(&conn.provider, &BackfillConfig::new(3, Some(3)), &sender, &SelectorDecoder, &Shutdown::new())
+                .await
+                .unwrap();
+            sender.close();
+            stats
+        });
+
+        let mut hashes = Vec::new();
+        while let Some(pending) = receiver.recv().await {
+            hashes.push(*pending.tx.inner.tx_hash());
+        }
+
+        assert_eq!(backfilling.await.unwrap().transactions, 6);
+        assert_eq!(hashes, mined);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_stops_on_shutdown() {
+        let node = MockNode::start().await;
+        node.add_block(1, vec![rpc_tx(7, 1)]);
+        let conn = establish_connection(&node.url).await.unwrap();
+
+        let shutdown = Shutdown::new();
+        shutdown.trigger();
+        let (sender, _receiver) = queue();
+        let stats = backfill(&conn.provider, &BackfillConfig::new(1, Some(1_000)), &sender, &SelectorDecoder, &shutdown)
+            .await
+            .unwrap();
+        assert_eq!(stats, BackfillStats::default());
+    }
+}
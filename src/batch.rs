@@ -0,0 +1,219 @@
+use crate::ingestor::SniperProvider;
+use crate::metrics::metrics;
+use alloy::primitives::B256;
+use alloy::providers::Provider;
+use alloy::rpc::client::{BatchRequest, Waiter};
+use alloy::rpc::types::Transaction as RpcTransaction;
+use alloy::transports::TransportResult;
+use std::time::Duration;
+use tokio::time::Instant;
+
+pub const DEFAULT_BATCH_SIZE: usize = 50;
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    pub max_size: usize,
+    pub window: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_BATCH_SIZE,
+            window: DEFAULT_BATCH_WINDOW,
+        }
+    }
+}
+
+impl BatchConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_size: std::env::var("FETCH_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_BATCH_SIZE)
+                .max(1),
+            window: std::env::var("FETCH_BATCH_WINDOW_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_BATCH_WINDOW),
+        }
+    }
+}
+
+pub struct Batcher<T> {
+    config: BatchConfig,
+    pending: Vec<T>,
+    opened_at: Option<Instant>,
+}
+
+impl<T> Batcher<T> {
+    pub fn new(config: BatchConfig) -> Self {
+        Self {
+            pending: Vec::with_capacity(config.max_size),
+            config,
+            opened_at: None,
+        }
+    }
+
+    pub fn push(&mut self, item: T) -> Option<Vec<T>> {
+        if self.pending.is_empty() {
+            self.opened_at = Some(Instant::now());
+        }
+        self.pending.push(item);
+
+        if self.pending.len() >= self.config.max_size || self.config.window.is_zero() {
+            Some(self.take())
+        } else {
+            None
+        }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.opened_at.map(|opened_at| opened_at + self.config.window)
+    }
+
+    pub fn take(&mut self) -> Vec<T> {
+        self.opened_at = None;
+        std::mem::replace(&mut self.pending, Vec::with_capacity(self.config.max_size))
+    }
+}
+
+pub async fn fetch_transactions(
+    provider: &SniperProvider,
+    hashes: &[B256],
+) -> TransportResult<Vec<TransportResult<Option<RpcTransaction>>>> {
+    metrics().fetch_batches.inc();
+    metrics().fetch_batched_hashes.add(hashes.len() as u64);
+
+    if let [hash] = hashes {
+        return Ok(vec![provider.get_transaction_by_hash(*hash).await]);
+    }
+
+    let mut batch = BatchRequest::new(provider.client());
+    let waiters = hashes
+        .iter()
+        .map(|hash| batch.add_call("eth_getTransactionByHash", &(hash,)))
+        .collect::<TransportResult<Vec<Waiter<Option<RpcTransaction>>>>>()?;
+
+    batch.send().await?;
+
+    let mut results = Vec::with_capacity(waiters.len());
+    for waiter in waiters {
+        results.push(waiter.await);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::rpc_tx;
+    use crate::mock_http::{response, MockHttp};
+    use alloy::consensus::Transaction;
+    use alloy::providers::ProviderBuilder;
+    use alloy::transports::RpcError;
+
+    fn config(max_size: usize, window_ms: u64) -> BatchConfig {
+        BatchConfig {
+            max_size,
+            window: Duration::from_millis(window_ms),
+        }
+    }
+
+    fn hash(n: u8) -> B256 {
+        B256::repeat_byte(n)
+    }
+
+    fn provider(url: &str) -> SniperProvider {
+        ProviderBuilder::new().on_http(url.parse().unwrap()).boxed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_batcher_flushes_on_size() {
+        let mut batcher = Batcher::new(config(3, 20));
+        assert_eq!(batcher.push(1), None);
+        assert_eq!(batcher.push(2), None);
+        assert_eq!(batcher.push(3), Some(vec![1, 2, 3]));
+        assert_eq!(batcher.deadline(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_batcher_deadline_starts_at_first_item() {
+        let mut batcher = Batcher::new(config(10, 20));
+        let opened = Instant::now();
+        batcher.push(1);
+        tokio::time::sleep(Duration::from_millis(15)).await;
+        batcher.push(2);
+
+        assert_eq!(batcher.deadline(), Some(opened + Duration::from_millis(20)));
+        assert_eq!(batcher.take(), vec![1, 2]);
+        assert_eq!(batcher.deadline(), None);
+    }
+
+    #[tokio::test]
+    async fn test_batcher_zero_window_disables_batching() {
+        let mut batcher = Batcher::new(config(10, 0));
+        assert_eq!(batcher.push(7), Some(vec![7]));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_splits_batch_response_per_hash() {
+        let body = serde_json::json!([
+            { "jsonrpc": "2.0", "id": 2, "error": { "code": -32005, "message": "rate limit exceeded" } },
+            { "jsonrpc": "2.0", "id": 1, "result": null },
+            { "jsonrpc": "2.0", "id": 0, "result": rpc_tx(9, 1) }
+        ]);
+        let mock = MockHttp::start(vec![response("200 OK", "", &body.to_string())]).await;
+
+        let results = fetch_transactions(&provider(&mock.url), &[hash(1), hash(2), hash(3)])
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().as_ref().unwrap().inner.nonce(), 9);
+        assert!(results[1].as_ref().unwrap().is_none());
+        assert!(matches!(&results[2], Err(RpcError::ErrorResp(e)) if e.code == -32005));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1, "one round trip for the whole batch");
+        let sent = requests[0].json();
+        let sent = sent.as_array().unwrap();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0]["method"], "eth_getTransactionByHash");
+        assert_eq!(sent[2]["params"][0], serde_json::json!(hash(3)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_single_hash_skips_batch_envelope() {
+        let body = serde_json::json!({ "jsonrpc": "2.0", "id": 0, "result": null });
+        let mock = MockHttp::start(vec![response("200 OK", "", &body.to_string())]).await;
+
+        let results = fetch_transactions(&provider(&mock.url), &[hash(4)]).await.unwrap();
+
+        assert!(results[0].as_ref().unwrap().is_none());
+        assert!(mock.requests()[0].json().is_object());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_missing_response_is_per_hash_error() {
+        let body = serde_json::json!([{ "jsonrpc": "2.0", "id": 0, "result": null }]);
+        let mock = MockHttp::start(vec![response("200 OK", "", &body.to_string())]).await;
+
+        let results = fetch_transactions(&provider(&mock.url), &[hash(5), hash(6)]).await.unwrap();
+
+        assert!(results[0].as_ref().unwrap().is_none());
+        assert!(results[1].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_transport_failure_fails_whole_batch() {
+        let mock = MockHttp::start(vec![response("429 Too Many Requests", "", "{}")]).await;
+
+        let result = fetch_transactions(&provider(&mock.url), &[hash(7), hash(8)]).await;
+
+        assert!(result.is_err());
+    }
+}
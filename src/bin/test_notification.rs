@@ -1,7 +1,10 @@
 use anyhow::{Context, Result};
 use dotenvy::dotenv;
 use mempool_sniper::action::{SniperAction, TargetTransaction};
-use mempool_sniper::discord::DiscordClient;
+use mempool_sniper::chain::{self, Explorer};
+use mempool_sniper::discord::{self, DiscordClient};
+use mempool_sniper::format::Formatter;
+use mempool_sniper::latency::Timings;
 use alloy::primitives::{Address, TxHash, U256};
 use std::env;
 
@@ -16,8 +19,14 @@ async fn main() -> Result<()> {
     println!("webhook url: {}...", &webhook_url[..50]);
     println!();
     
-    let client = DiscordClient::new(&webhook_url)
+    let formatter = Formatter::load(
+        "discord",
+        discord::DEFAULT_TEMPLATE,
+        &Explorer::from_env(chain::SEPOLIA),
+    )?;
+    let client = DiscordClient::new(&webhook_url, formatter)
         .context("Failed to create Discord client")?;
+    client.start().await?;
     
     let fake_tx = TargetTransaction {
         tx_hash: TxHash::from([
@@ -56,6 +65,8 @@ async fn main() -> Result<()> {
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_millis() as u64,
+        block_number: None,
+        timings: Timings::default(),
     };
     
     println!("simulated whale transaction:");
@@ -67,7 +78,12 @@ async fn main() -> Result<()> {
     println!();
     
     println!("sending discord webhook...");
-    match client.execute(&fake_tx).await {
+    let result = match client.execute(&fake_tx).await {
+        Ok(_) => client.flush(std::time::Duration::from_secs(30)).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
         Ok(_) => {
             println!("success! discord alert sent!");
             println!("check your discord channel for the whale alert!");
@@ -0,0 +1,126 @@
+use alloy::primitives::{Address, TxHash};
+
+pub const MAINNET: u64 = 1;
+pub const SEPOLIA: u64 = 11_155_111;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chain {
+    pub id: u64,
+    pub name: &'static str,
+    pub explorer: &'static str,
+}
+
+const CHAINS: &[Chain] = &[
+    Chain { id: MAINNET, name: "mainnet", explorer: "https://etherscan.io" },
+    Chain { id: SEPOLIA, name: "sepolia", explorer: "https://sepolia.etherscan.io" },
+    Chain { id: 17_000, name: "holesky", explorer: "https://holesky.etherscan.io" },
+    Chain { id: 10, name: "optimism", explorer: "https://optimistic.etherscan.io" },
+    Chain { id: 56, name: "bsc", explorer: "https://bscscan.com" },
+    Chain { id: 137, name: "polygon", explorer: "https://polygonscan.com" },
+    Chain { id: 8_453, name: "base", explorer: "https://basescan.org" },
+    Chain { id: 42_161, name: "arbitrum", explorer: "https://arbiscan.io" },
+    Chain { id: 43_114, name: "avalanche", explorer: "https://snowtrace.io" },
+];
+
+pub fn lookup(chain_id: u64) -> Option<&'static Chain> {
+    CHAINS.iter().find(|c| c.id == chain_id)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Explorer {
+    base_url: Option<String>,
+}
+
+impl Explorer {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: Some(base_url.trim_end_matches('/').to_string()),
+        }
+    }
+
+    pub fn for_chain(chain_id: u64) -> Self {
+        match lookup(chain_id) {
+            Some(chain) => Self::new(chain.explorer),
+            None => Self::default(),
+        }
+    }
+
+    pub fn from_env(chain_id: u64) -> Self {
+        match std::env::var("EXPLORER_URL") {
+            Ok(url) if !url.is_empty() => Self::new(&url),
+            _ => Self::for_chain(chain_id),
+        }
+    }
+
+    pub fn tx_url(&self, hash: &TxHash) -> Option<String> {
+        self.url("tx", &format!("{:?}", hash))
+    }
+
+    pub fn address_url(&self, address: &Address) -> Option<String> {
+        self.url("address", &format!("{:#x}", address))
+    }
+
+    pub fn token_url(&self, token: &Address) -> Option<String> {
+        self.url("token", &format!("{:#x}", token))
+    }
+
+    fn url(&self, kind: &str, id: &str) -> Option<String> {
+        self.base_url
+            .as_ref()
+            .map(|base| format!("{}/{}/{}", base, kind, id))
+    }
+}
+
+pub fn short_hash(hash: &TxHash) -> String {
+    let full = format!("{:?}", hash);
+    format!("{}...{}", &full[..10], &full[full.len() - 8..])
+}
+
+pub fn short_address(address: &Address) -> String {
+    let full = format!("{:#x}", address);
+    format!("{}...{}", &full[..6], &full[full.len() - 4..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lookup() {
+        assert_eq!(lookup(SEPOLIA).unwrap().name, "sepolia");
+        assert_eq!(lookup(MAINNET).unwrap().explorer, "https://etherscan.io");
+        assert!(lookup(999_999).is_none());
+    }
+
+    #[test]
+    fn test_explorer_urls() {
+        let explorer = Explorer::for_chain(8_453);
+        let hash = TxHash::from([0xab; 32]);
+        let token = Address::from([0x11; 20]);
+
+        assert_eq!(
+            explorer.tx_url(&hash).unwrap(),
+            format!("https://basescan.org/tx/0x{}", "ab".repeat(32))
+        );
+        assert_eq!(
+            explorer.address_url(&token).unwrap(),
+            format!("https://basescan.org/address/0x{}", "11".repeat(20))
+        );
+        assert_eq!(
+            explorer.token_url(&token).unwrap(),
+            format!("https://basescan.org/token/0x{}", "11".repeat(20))
+        );
+
+        assert!(Explorer::new("https://explorer.local/")
+            .tx_url(&hash)
+            .unwrap()
+            .starts_with("https://explorer.local/tx/0x"));
+        assert!(Explorer::for_chain(999_999).tx_url(&hash).is_none());
+    }
+
+    #[test]
+    fn test_short_forms() {
+        assert_eq!(short_hash(&TxHash::from([0xab; 32])), "0xabababab...abababab");
+        assert_eq!(short_address(&Address::from([0x11; 20])), "0x1111...1111");
+    }
+}
@@ -87,8 +87,25 @@ pub const AGGREGATOR_SWAP: [u8; 4] = [0x12, 0xaa, 0x3c, 0xaf];
 
 pub const UNISWAP_V3_SWAP_TO: [u8; 4] = [0xbc, 0x65, 0x1e, 0x96];
 
-pub fn is_target_transaction(_input_data: &[u8]) -> bool {
-    true
+pub const TARGET_SELECTORS: [[u8; 4]; 10] = [
+    SWAP_EXACT_ETH_FOR_TOKENS,
+    SWAP_EXACT_TOKENS_FOR_ETH,
+    SWAP_EXACT_TOKENS_FOR_TOKENS,
+    SWAP_ETH_FOR_EXACT_TOKENS,
+    EXACT_INPUT_SINGLE,
+    EXACT_INPUT,
+    MULTICALL_V3,
+    EXECUTE_V3,
+    AGGREGATOR_SWAP,
+    UNISWAP_V3_SWAP_TO,
+];
+
+pub fn is_target_transaction(input_data: &[u8]) -> bool {
+    if input_data.len() < 4 {
+        return false;
+    }
+
+    TARGET_SELECTORS.iter().any(|s| s[..] == input_data[0..4])
 }
 
 pub fn decode_transaction(input_data: &[u8], tx_value: U256) -> Result<DecodedTx> {
@@ -266,6 +283,27 @@ pub fn decode_transaction(input_data: &[u8], tx_value: U256) -> Result<DecodedTx
     })
 }
 
+pub trait Decoder: Send + Sync {
+    fn decode(&self, input_data: &[u8], tx_value: U256) -> Result<DecodedTx>;
+
+    fn selects(&self, _input_data: &[u8]) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SelectorDecoder;
+
+impl Decoder for SelectorDecoder {
+    fn decode(&self, input_data: &[u8], tx_value: U256) -> Result<DecodedTx> {
+        decode_transaction(input_data, tx_value)
+    }
+
+    fn selects(&self, input_data: &[u8]) -> bool {
+        is_target_transaction(input_data)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,120 @@
+use alloy::primitives::TxHash;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_CAPACITY: usize = 100_000;
+pub const DEFAULT_TTL_SECS: u64 = 600;
+
+pub struct SeenSet {
+    capacity: usize,
+    ttl: Duration,
+    order: VecDeque<(TxHash, Instant)>,
+    seen: HashSet<TxHash>,
+}
+
+impl SeenSet {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            ttl,
+            order: VecDeque::with_capacity(capacity),
+            seen: HashSet::with_capacity(capacity),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("DEDUP_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        let ttl_secs = std::env::var("DEDUP_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_TTL_SECS);
+
+        Self::new(capacity, Duration::from_secs(ttl_secs))
+    }
+
+    pub fn insert(&mut self, hash: TxHash) -> bool {
+        self.insert_at(hash, Instant::now())
+    }
+
+    fn insert_at(&mut self, hash: TxHash, now: Instant) -> bool {
+        self.evict_expired(now);
+
+        if self.seen.contains(&hash) {
+            return false;
+        }
+
+        if self.order.len() >= self.capacity {
+            if let Some((oldest, _)) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+
+        self.order.push_back((hash, now));
+        self.seen.insert(hash);
+        true
+    }
+
+    fn evict_expired(&mut self, now: Instant) {
+        while let Some((hash, inserted_at)) = self.order.front().copied() {
+            if now.duration_since(inserted_at) < self.ttl {
+                break;
+            }
+            self.order.pop_front();
+            self.seen.remove(&hash);
+        }
+    }
+
+    pub fn tracked(&self) -> usize {
+        self.seen.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(byte: u8) -> TxHash {
+        TxHash::from([byte; 32])
+    }
+
+    #[test]
+    fn test_duplicate_rejected_within_window() {
+        let mut seen = SeenSet::new(16, Duration::from_secs(60));
+
+        assert!(seen.insert(hash(1)));
+        assert!(!seen.insert(hash(1)), "second insert should be a duplicate");
+        assert!(seen.insert(hash(2)));
+        assert_eq!(seen.tracked(), 2);
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let mut seen = SeenSet::new(2, Duration::from_secs(60));
+
+        assert!(seen.insert(hash(1)));
+        assert!(seen.insert(hash(2)));
+        assert!(seen.insert(hash(3)));
+
+        assert_eq!(seen.tracked(), 2);
+        assert!(seen.insert(hash(1)), "evicted hash should be accepted again");
+        assert!(!seen.insert(hash(3)));
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let mut seen = SeenSet::new(16, Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert!(seen.insert_at(hash(1), start));
+        assert!(!seen.insert_at(hash(1), start + Duration::from_secs(5)));
+        assert!(
+            seen.insert_at(hash(1), start + Duration::from_secs(11)),
+            "hash should be accepted again once the window has passed"
+        );
+        assert_eq!(seen.tracked(), 1);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::action::TargetTransaction;
+use alloy::primitives::{Address, U256};
+use std::collections::HashMap;
+
+pub const DEFAULT_TOP_N: usize = 5;
+
+#[derive(Debug, Default)]
+pub struct Digest {
+    pub total: u64,
+    pub total_value: U256,
+    by_method: HashMap<String, u64>,
+    tokens: HashMap<Address, u64>,
+    top: Vec<TargetTransaction>,
+}
+
+impl Digest {
+    pub fn record(&mut self, tx: &TargetTransaction, top_n: usize) {
+        self.total += 1;
+        self.total_value = self.total_value.saturating_add(tx.value);
+        *self.by_method.entry(tx.method.clone()).or_insert(0) += 1;
+
+        for token in &tx.path {
+            *self.tokens.entry(*token).or_insert(0) += 1;
+        }
+
+        if top_n == 0 {
+            return;
+        }
+
+        if self.top.len() < top_n {
+            self.top.push(tx.clone());
+        } else if let Some(lowest) = self.top.iter_mut().min_by_key(|t| t.value) {
+            if tx.value > lowest.value {
+                *lowest = tx.clone();
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: Digest, top_n: usize) {
+        self.total += other.total;
+        self.total_value = self.total_value.saturating_add(other.total_value);
+
+        for (method, count) in other.by_method {
+            *self.by_method.entry(method).or_insert(0) += count;
+        }
+        for (token, count) in other.tokens {
+            *self.tokens.entry(token).or_insert(0) += count;
+        }
+
+        self.top.extend(other.top);
+        self.top.sort_by_key(|t| std::cmp::Reverse(t.value));
+        self.top.truncate(top_n);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    pub fn methods(&self) -> Vec<(&str, u64)> {
+        let mut methods: Vec<_> = self.by_method.iter().map(|(m, c)| (m.as_str(), *c)).collect();
+        methods.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        methods
+    }
+
+    pub fn top_by_value(&self) -> Vec<&TargetTransaction> {
+        let mut top: Vec<_> = self.top.iter().collect();
+        top.sort_by_key(|t| std::cmp::Reverse(t.value));
+        top
+    }
+
+    pub fn top_tokens(&self, n: usize) -> Vec<(Address, u64)> {
+        let mut tokens: Vec<_> = self.tokens.iter().map(|(a, c)| (*a, *c)).collect();
+        tokens.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tokens.truncate(n);
+        tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::target_tx;
+
+    fn tx(id: u8, value: u64, method: &str, path: Vec<Address>) -> TargetTransaction {
+        TargetTransaction {
+            value: U256::from(value),
+            method: method.to_string(),
+            path,
+            ..target_tx(id)
+        }
+    }
+
+    #[test]
+    fn test_digest_aggregation() {
+        let weth = Address::from([0xc0; 20]);
+        let usdt = Address::from([0xda; 20]);
+        let mut digest = Digest::default();
+
+        digest.record(&tx(1, 10, "swapExactETHForTokens", vec![weth, usdt]), 2);
+        digest.record(&tx(2, 50, "swapExactETHForTokens", vec![weth]), 2);
+        digest.record(&tx(3, 5, "Native Transfer", vec![]), 2);
+        digest.record(&tx(4, 30, "exactInputSingle", vec![weth, usdt]), 2);
+
+        assert_eq!(digest.total, 4);
+        assert_eq!(digest.total_value, U256::from(95u64));
+        assert_eq!(
+            digest.methods(),
+            vec![("swapExactETHForTokens", 2), ("Native Transfer", 1), ("exactInputSingle", 1)]
+        );
+
+        let top: Vec<_> = digest.top_by_value().iter().map(|t| t.value).collect();
+        assert_eq!(top, vec![U256::from(50u64), U256::from(30u64)]);
+
+        assert_eq!(digest.top_tokens(1), vec![(weth, 3)]);
+    }
+
+    #[test]
+    fn test_merge_keeps_counts_and_top_values() {
+        let weth = Address::from([0xc0; 20]);
+        let mut unsent = Digest::default();
+        unsent.record(&tx(1, 10, "swapExactETHForTokens", vec![weth]), 2);
+        unsent.record(&tx(2, 40, "Native Transfer", vec![]), 2);
+
+        let mut current = Digest::default();
+        current.record(&tx(3, 20, "swapExactETHForTokens", vec![weth]), 2);
+        current.merge(unsent, 2);
+
+        assert_eq!(current.total, 3);
+        assert_eq!(current.total_value, U256::from(70u64));
+        assert_eq!(current.methods(), vec![("swapExactETHForTokens", 2), ("Native Transfer", 1)]);
+        assert_eq!(current.top_tokens(1), vec![(weth, 2)]);
+
+        let top: Vec<_> = current.top_by_value().iter().map(|t| t.value).collect();
+        assert_eq!(top, vec![U256::from(40u64), U256::from(20u64)]);
+    }
+
+    #[test]
+    fn test_empty_digest() {
+        let digest = Digest::default();
+        assert!(digest.is_empty());
+        assert!(digest.top_by_value().is_empty());
+    }
+}
@@ -1,149 +1,835 @@
 use crate::action::{SniperAction, TargetTransaction};
-use anyhow::{Context, Result};
+use crate::alert_queue::{AlertQueue, PushOutcome};
+use crate::digest::{self, Digest};
+use crate::format::{self, AlertView, Formatter, Link};
+use crate::metrics::metrics;
+use crate::template::Markup;
+use anyhow::Result;
 use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
 use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 
+const DEFAULT_REQUESTS_PER_MINUTE: u32 = 25;
+const MAX_SEND_ATTEMPTS: u32 = 5;
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+const MAX_EMBED_CHARS_PER_MESSAGE: usize = 6000;
+const DEFAULT_DIGEST_INTERVAL_SECS: u64 = 300;
+
+pub const DEFAULT_TEMPLATE: &str = "**value:** **{{value}} eth**
+**status:** {{status}}{{#if block}} in block {{block}}{{/if}}
+**method:** {{method | code}}
+**transaction:** {{tx}}
+**from:** {{from}}
+**to:** {{to}}
+{{#if path}}**path:** {{path}}
+{{/if}}**detected:** <t:{{detected_at}}:R>";
+
 struct RateLimiter {
     last_request: Instant,
     min_interval: Duration,
+    next_interval: Duration,
+    blocked_until: Option<Instant>,
 }
 
 impl RateLimiter {
     fn new(requests_per_minute: u32) -> Self {
-        let min_interval = Duration::from_secs(60) / requests_per_minute;
+        let min_interval = Duration::from_secs(60) / requests_per_minute.max(1);
         Self {
             last_request: Instant::now() - min_interval,
             min_interval,
+            next_interval: min_interval,
+            blocked_until: None,
         }
     }
 
     async fn acquire(&mut self) {
-        let elapsed = self.last_request.elapsed();
-        if elapsed < self.min_interval {
-            let wait_time = self.min_interval - elapsed;
-            tokio::time::sleep(wait_time).await;
+        let mut ready_at = self.last_request + self.next_interval;
+        if let Some(blocked_until) = self.blocked_until.take() {
+            ready_at = ready_at.max(blocked_until);
         }
+
+        tokio::time::sleep_until(ready_at).await;
         self.last_request = Instant::now();
     }
+
+    fn update(&mut self, limits: &BucketState) {
+        self.next_interval = self.min_interval;
+
+        if let (Some(remaining), Some(reset_after)) = (limits.remaining, limits.reset_after) {
+            if remaining == 0 {
+                self.pause(reset_after);
+            } else {
+                let spread = reset_after / remaining;
+                self.next_interval = self.next_interval.max(spread);
+            }
+        }
+    }
+
+    fn pause(&mut self, duration: Duration) {
+        let until = Instant::now() + duration;
+        self.blocked_until = Some(self.blocked_until.map_or(until, |b| b.max(until)));
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct BucketState {
+    remaining: Option<u32>,
+    reset_after: Option<Duration>,
+    retry_after: Option<Duration>,
+}
+
+impl BucketState {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let secs = |v: &str| v.parse::<f64>().ok().filter(|s| *s >= 0.0).map(Duration::from_secs_f64);
+
+        Self {
+            remaining: header("x-ratelimit-remaining").and_then(|v| v.parse().ok()),
+            reset_after: header("x-ratelimit-reset-after").and_then(secs),
+            retry_after: header("retry-after").and_then(secs),
+        }
+    }
 }
 
+fn retry_after_from_body(body: &str) -> Option<Duration> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let secs = value.get("retry_after")?.as_f64()?;
+    (secs >= 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertMode {
+    Realtime,
+    Digest,
+    Both,
+}
+
+impl AlertMode {
+    pub fn from_str_lossy(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "digest" => AlertMode::Digest,
+            "both" => AlertMode::Both,
+            _ => AlertMode::Realtime,
+        }
+    }
+
+    fn realtime(self) -> bool {
+        matches!(self, AlertMode::Realtime | AlertMode::Both)
+    }
+
+    fn digest(self) -> bool {
+        matches!(self, AlertMode::Digest | AlertMode::Both)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscordConfig {
+    pub requests_per_minute: u32,
+    pub mode: AlertMode,
+    pub batch_size: usize,
+    pub batch_window: Duration,
+    pub digest_interval: Duration,
+    pub digest_top_n: usize,
+    pub formatter: Formatter,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: DEFAULT_REQUESTS_PER_MINUTE,
+            mode: AlertMode::Realtime,
+            batch_size: MAX_EMBEDS_PER_MESSAGE,
+            batch_window: Duration::ZERO,
+            digest_interval: Duration::from_secs(DEFAULT_DIGEST_INTERVAL_SECS),
+            digest_top_n: digest::DEFAULT_TOP_N,
+            formatter: Formatter::with_default(DEFAULT_TEMPLATE),
+        }
+    }
+}
+
+impl DiscordConfig {
+    pub fn from_env(formatter: Formatter) -> Self {
+        let var = |name: &str| std::env::var(format!("DISCORD_{}", name)).ok();
+        let num = |name: &str| var(name).and_then(|v| v.parse::<u64>().ok());
+        let defaults = Self::default();
+
+        Self {
+            requests_per_minute: num("REQUESTS_PER_MINUTE")
+                .map(|v| v as u32)
+                .unwrap_or(defaults.requests_per_minute),
+            mode: var("MODE")
+                .map(|v| AlertMode::from_str_lossy(&v))
+                .unwrap_or(defaults.mode),
+            batch_size: num("BATCH_SIZE")
+                .map(|v| (v as usize).clamp(1, MAX_EMBEDS_PER_MESSAGE))
+                .unwrap_or(defaults.batch_size),
+            batch_window: num("BATCH_WINDOW_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.batch_window),
+            digest_interval: num("DIGEST_INTERVAL_SECS")
+                .map(|v| Duration::from_secs(v.max(1)))
+                .unwrap_or(defaults.digest_interval),
+            digest_top_n: num("DIGEST_TOP_N")
+                .map(|v| v as usize)
+                .unwrap_or(defaults.digest_top_n),
+            formatter,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct DiscordClient {
     webhook_url: String,
     client: reqwest::Client,
     rate_limiter: Arc<Mutex<RateLimiter>>,
+    queue: Arc<AlertQueue>,
+    config: DiscordConfig,
+    digest: Arc<std::sync::Mutex<Digest>>,
+    started: Arc<AtomicBool>,
 }
 
 impl DiscordClient {
-    pub fn new(webhook_url: &str) -> Result<Self> {
-        tracing::info!("discord webhook client initialized");
+    pub fn new(webhook_url: &str, formatter: Formatter) -> Result<Self> {
+        let config = DiscordConfig::from_env(formatter);
+        let queue = AlertQueue::from_env().with_depth(metrics().alert_queue_depth.with_label("discord"));
+        Self::with_config(webhook_url, config, queue)
+    }
+
+    pub fn with_config(webhook_url: &str, config: DiscordConfig, queue: AlertQueue) -> Result<Self> {
+        tracing::info!(
+            "discord webhook client initialized (mode: {:?}, batch size: {})",
+            config.mode,
+            config.batch_size
+        );
         
-        Ok(Self {
+        let client = Self {
             webhook_url: webhook_url.to_string(),
             client: reqwest::Client::new(),
-            rate_limiter: Arc::new(Mutex::new(RateLimiter::new(25))),
-            margin
-        })
+            rate_limiter: Arc::new(Mutex::new(RateLimiter::new(config.requests_per_minute))),
+            queue: Arc::new(queue),
+            config,
+            digest: Arc::new(std::sync::Mutex::new(Digest::default())),
+            started: Arc::new(AtomicBool::new(false)),
+        };
+
+        Ok(client)
     }
 
-    async fn send_alert(&self, tx: &TargetTransaction) -> Result<()> {
-        self.rate_limiter.lock().await.acquire().await;
+    async fn run_sender(self) {
+        loop {
+            let first = self.queue.pop().await;
+            let mut batch = vec![first];
 
-        let eth_value = tx.value.to_string().parse::<f64>().unwrap_or(0.0) / 1e18;
-        
-        let etherscan_link = format!("https://sepolia.etherscan.io/tx/{:?}", tx.tx_hash);
-        let tx_hash_short = format!("{:?}", tx.tx_hash);
-        let tx_display = format!("[{}...{}]({})", 
-            &tx_hash_short[0..10], 
-            &tx_hash_short[tx_hash_short.len()-8..],
-            etherscan_link
-        );
-        
-        let to_display = format!("{:#x}", tx.to);
+            if self.config.batch_size > 1 {
+                if !self.config.batch_window.is_zero() {
+                    tokio::time::sleep(self.config.batch_window).await;
+                }
+                while batch.len() < self.config.batch_size {
+                    match self.queue.try_pop() {
+                        Some(tx) => batch.push(tx),
+                        None => break,
+                    }
+                }
+            }
+
+            self.deliver(&batch).await;
+
+            for _ in &batch {
+                self.queue.done();
+            }
+        }
+    }
+
+    async fn run_digest(self) {
+        let mut interval = tokio::time::interval(self.config.digest_interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            let _ = self.send_digest().await;
+        }
+    }
+
+    async fn send_digest(&self) -> Result<()> {
+        let digest = std::mem::take(&mut *self.digest.lock().unwrap());
+        if digest.is_empty() {
+            return Ok(());
+        }
 
         let payload = json!({
-            "embeds": [{
-                "title": "transaction detected",
-                "color": 0x00ff00,
-                "fields": [
-                    {
-                        "name": "value",
-                        "value": format!("**{:.4} eth**", eth_value),
-                        "inline": true
-                    },
-                    {
-                        "name": "method",
-                        "value": format!("`{}`", tx.method),
-                        "inline": true
-                    },
-                    {
-                        "name": "transaction",
-                        "value": tx_display,
-                        "inline": false
-                    },
-                    {
-                        "name": "from",
-                        "value": format!("`{:#x}`", tx.from),
-                        "inline": true
-                    },
-                    {
-                        "name": "to",
-                        "value": format!("`{}`", to_display),
-                        "inline": true
-                    },
-                    {
-                        "name": "detected",
-                        "value": format!("<t:{}:R>", tx.detected_at / 1000),
-                        "inline": false
-                    }
-                ],
-                "footer": {
-                    "text": "mempool sniper - real-time monitor"
-                },
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }]
+            "embeds": [build_digest_embed(&digest, self.config.digest_interval, &self.config.formatter)]
         });
 
-        let response = self
-            .client
-            .post(&self.webhook_url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await
-            .context("failed to send discord webhook")?;
+        match self.post(&payload).await {
+            Ok(()) => {
+                tracing::info!("discord digest sent ({} transactions)", digest.total);
+                Ok(())
+            }
+            Err(e) => {
+                metrics().discord_send_failures.inc();
+                tracing::error!("failed to send discord digest: {} - keeping it for the next window", e);
+                self.digest.lock().unwrap().merge(digest, self.config.digest_top_n);
+                Err(e)
+            }
+        }
+    }
+
+    async fn deliver(&self, batch: &[TargetTransaction]) {
+        let embeds = self.embeds(batch);
+        let mut start = 0;
+
+        while start < batch.len() {
+            let end = start + message_len(&embeds[start..]);
+            let sent = &batch[start..end];
 
-        if response.status().is_success() {
+            match self.send_batch(sent, &embeds[start..end]).await {
+                Ok(()) => metrics().alerts_delivered.add(sent.len() as u64),
+                Err(e) => {
+                    metrics().discord_send_failures.inc();
+                    tracing::error!("failed to send {} discord alerts: {}", sent.len(), e);
+                }
+            }
+
+            start = end;
+        }
+    }
+
+    fn embeds(&self, batch: &[TargetTransaction]) -> Vec<serde_json::Value> {
+        batch
+            .iter()
+            .map(|tx| {
+                let view = self.config.formatter.view(tx);
+                build_embed(&view, self.config.formatter.render(&view, &Markdown))
+            })
+            .collect()
+    }
+
+    async fn send_batch(&self, batch: &[TargetTransaction], embeds: &[serde_json::Value]) -> Result<()> {
+        let payload = json!({ "embeds": embeds });
+
+        self.post(&payload).await?;
+
+        for tx in batch {
             tracing::info!("discord alert sent for tx {:?}", tx.tx_hash);
-            Ok(())
-        } else {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!(
-                "discord webhook failed: {} - {}",
-                status,
-                error_text
-            )
+        }
+
+        Ok(())
+    }
+
+    async fn post(&self, payload: &serde_json::Value) -> Result<()> {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            self.rate_limiter.lock().await.acquire().await;
+
+            let result = self
+                .client
+                .post(&self.webhook_url)
+                .header("Content-Type", "application/json")
+                .json(payload)
+                .send()
+                .await;
+
+            let (error, retry_after) = match result {
+                Ok(response) => {
+                    let status = response.status();
+                    let limits = BucketState::from_headers(response.headers());
+                    self.rate_limiter.lock().await.update(&limits);
+
+                    if status.is_success() {
+                        return Ok(());
+                    }
+
+                    let error_text = response.text().await.unwrap_or_default();
+                    let error = anyhow::anyhow!("discord webhook failed: {} - {}", status, error_text);
+
+                    if status == StatusCode::TOO_MANY_REQUESTS {
+                        let retry_after = retry_after_from_body(&error_text)
+                            .or(limits.retry_after)
+                            .or(limits.reset_after)
+                            .unwrap_or(DEFAULT_RETRY_AFTER);
+                        (error, Some(retry_after))
+                    } else if status.is_server_error() {
+                        (error, None)
+                    } else {
+                        return Err(error);
+                    }
+                }
+                Err(e) => (anyhow::Error::new(e).context("failed to send discord webhook"), None),
+            };
+
+            if attempt >= MAX_SEND_ATTEMPTS {
+                return Err(error.context(format!("gave up after {} attempts", attempt)));
+            }
+
+            metrics().alerts_retried.inc();
+            match retry_after {
+                Some(retry_after) => {
+                    tracing::warn!(
+                        "discord rate limited, retrying in {:?} (attempt {}/{})",
+                        retry_after,
+                        attempt,
+                        MAX_SEND_ATTEMPTS
+                    );
+                    self.rate_limiter.lock().await.pause(retry_after);
+                }
+                None => {
+                    let backoff = Duration::from_secs(1)
+                        .saturating_mul(2u32.saturating_pow(attempt - 1))
+                        .min(MAX_BACKOFF);
+                    tracing::warn!(
+                        "discord delivery failed: {} - retrying in {:?} (attempt {}/{})",
+                        error,
+                        backoff,
+                        attempt,
+                        MAX_SEND_ATTEMPTS
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+            }
+        }
+    }
+}
+
+struct Markdown;
+
+impl Markup for Markdown {
+    fn escape(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(c, '*' | '_' | '~' | '|' | '`' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    fn code(&self, text: &str) -> String {
+        format!("`{}`", text.replace('`', "'"))
+    }
+
+    fn link(&self, link: &Link) -> String {
+        match &link.url {
+            Some(url) => format!("[{}]({})", link.short, url),
+            None => format!("`{}`", link.full),
         }
     }
 }
 
+fn embed_chars(embed: &serde_json::Value) -> usize {
+    let chars = |value: &serde_json::Value| value.as_str().map_or(0, |s| s.chars().count());
+
+    let fields = embed["fields"]
+        .as_array()
+        .map_or(0, |fields| fields.iter().map(|f| chars(&f["name"]) + chars(&f["value"])).sum());
+
+    chars(&embed["title"]) + chars(&embed["description"]) + chars(&embed["footer"]["text"]) + chars(&embed["author"]["name"]) + fields
+}
+
+fn message_len(embeds: &[serde_json::Value]) -> usize {
+    let mut total = 0;
+
+    for (i, embed) in embeds.iter().enumerate() {
+        total += embed_chars(embed);
+        if i > 0 && total > MAX_EMBED_CHARS_PER_MESSAGE {
+            return i;
+        }
+    }
+
+    embeds.len()
+}
+
+fn build_embed(view: &AlertView, description: String) -> serde_json::Value {
+    json!({
+        "title": view.title(),
+        "color": 0x00ff00,
+        "description": description,
+        "url": view.tx.url,
+        "footer": {
+            "text": "mempool sniper - real-time monitor"
+        },
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })
+}
+
+fn build_digest_embed(digest: &Digest, interval: Duration, formatter: &Formatter) -> serde_json::Value {
+    let or_none = |lines: Vec<String>| {
+        if lines.is_empty() {
+            "none".to_string()
+        } else {
+            lines.join("\n")
+        }
+    };
+
+    let methods = or_none(
+        digest
+            .methods()
+            .iter()
+            .take(10)
+            .map(|(method, count)| format!("`{}`: {}", method, count))
+            .collect(),
+    );
+
+    let top = or_none(
+        digest
+            .top_by_value()
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| {
+                let view = formatter.view(tx);
+                format!("{}. {} - **{} eth** (`{}`)", i + 1, Markdown.link(&view.tx), view.value_eth, view.method)
+            })
+            .collect(),
+    );
+
+    let tokens = or_none(
+        digest
+            .top_tokens(5)
+            .iter()
+            .map(|(token, count)| format!("{}: {}", Markdown.link(&format::token_link(token, &formatter.explorer)), count))
+            .collect(),
+    );
+
+    json!({
+        "title": format!("mempool digest - last {}s", interval.as_secs()),
+        "color": 0x3498db,
+        "description": format!("**{}** transactions | **{} eth** total", digest.total, format::format_eth(digest.total_value)),
+        "fields": [
+            { "name": "by method", "value": methods, "inline": false },
+            { "name": "top by value", "value": top, "inline": false },
+            { "name": "top tokens", "value": tokens, "inline": false }
+        ],
+        "footer": {
+            "text": "mempool sniper - digest"
+        },
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    })
+}
+
 #[async_trait]
 impl SniperAction for DiscordClient {
+    async fn start(&self) -> Result<()> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        tokio::spawn(self.clone().run_sender());
+
+        if self.config.mode.digest() {
+            tokio::spawn(self.clone().run_digest());
+        }
+
+        Ok(())
+    }
+
     async fn execute(&self, tx: &TargetTransaction) -> Result<()> {
         tracing::info!(
-            "target detected: {:?} | {:.4} eth | {}",
+            "target detected: {:?} | {} eth | {}",
             tx.tx_hash,
-            tx.value.to_string().parse::<f64>().unwrap_or(0.0) / 1e18,
+            format::format_eth(tx.value),
             tx.method
         );
 
-        if let Err(e) = self.send_alert(tx).await {
-            tracing::error!("failed to send discord alert: {}", e);
+        if self.config.mode.digest() {
+            self.digest.lock().unwrap().record(tx, self.config.digest_top_n);
+        }
+
+        if !self.config.mode.realtime() {
+            return Ok(());
+        }
+
+        match self.queue.push(tx.clone()).await {
+            PushOutcome::Queued | PushOutcome::Spilled => {}
+            PushOutcome::Evicted => {
+                tracing::debug!("discord queue full - evicted lowest value alert");
+            }
+            PushOutcome::Dropped => {
+                tracing::debug!("discord queue full - dropped alert for tx {:?}", tx.tx_hash);
+            }
         }
 
         Ok(())
     }
+
+    async fn flush(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+
+        if !self.queue.wait_idle(timeout).await {
+            let pending = self.queue.depth();
+            let spilled = self.queue.spill_pending().await?;
+            anyhow::bail!(
+                "{} discord alerts still queued after {:?} ({} saved to spill file)",
+                pending,
+                timeout,
+                spilled
+            );
+        }
+
+        if self.config.mode.digest() {
+            tokio::time::timeout_at(deadline, self.send_digest())
+                .await
+                .map_err(|_| anyhow::anyhow!("discord digest not sent within {:?}", timeout))??;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample_tx;
+    use crate::chain::{self, Explorer};
+    use alloy::primitives::Address;
+    use crate::mock_http::{response, MockHttp};
+    use reqwest::header::HeaderValue;
+
+    async fn fast_client(url: &str, config: DiscordConfig) -> DiscordClient {
+        let config = DiscordConfig {
+            requests_per_minute: 60_000,
+            ..config
+        };
+        let queue = AlertQueue::new(100, crate::alert_queue::DropPolicy::DropLowestValue, None);
+        let client = DiscordClient::with_config(url, config, queue).unwrap();
+        client.start().await.unwrap();
+        client
+    }
+
+    #[test]
+    fn test_bucket_state_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset-after", HeaderValue::from_static("1.5"));
+        headers.insert("retry-after", HeaderValue::from_static("2"));
+
+        let limits = BucketState::from_headers(&headers);
+        assert_eq!(limits.remaining, Some(0));
+        assert_eq!(limits.reset_after, Some(Duration::from_millis(1500)));
+        assert_eq!(limits.retry_after, Some(Duration::from_secs(2)));
+
+        assert_eq!(BucketState::from_headers(&HeaderMap::new()), BucketState::default());
+    }
+
+    #[test]
+    fn test_retry_after_from_body() {
+        assert_eq!(
+            retry_after_from_body(r#"{"message": "You are being rate limited.", "retry_after": 0.25, "global": false}"#),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(retry_after_from_body("not json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_pauses_when_bucket_empty() {
+        let mut limiter = RateLimiter::new(60_000);
+        limiter.update(&BucketState {
+            remaining: Some(0),
+            reset_after: Some(Duration::from_millis(100)),
+            retry_after: None,
+        });
+
+        let started = Instant::now();
+        limiter.acquire().await;
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_retries_after_429() {
+        let server = MockHttp::start(vec![
+            response(
+                "429 Too Many Requests",
+                "",
+                r#"{"message": "You are being rate limited.", "retry_after": 0.05, "global": false}"#,
+            ),
+            response("204 No Content", "X-RateLimit-Remaining: 4\r\nX-RateLimit-Reset-After: 1\r\n", ""),
+        ])
+        .await;
+
+        let client = fast_client(&server.url, DiscordConfig::default()).await;
+        let batch = [sample_tx()];
+        let result = client.send_batch(&batch, &client.embeds(&batch)).await;
+
+        assert!(result.is_ok(), "alert should be delivered after retry: {:?}", result);
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_shares_one_attempt_budget() {
+        let limited = r#"{"message": "You are being rate limited.", "retry_after": 0.01, "global": false}"#;
+        let server = MockHttp::start(vec![response("429 Too Many Requests", "", limited); MAX_SEND_ATTEMPTS as usize + 1]).await;
+
+        let client = fast_client(&server.url, DiscordConfig::default()).await;
+        client.deliver(&[sample_tx()]).await;
+
+        assert_eq!(server.hits(), MAX_SEND_ATTEMPTS as usize);
+    }
+
+    #[tokio::test]
+    async fn test_execute_delivers_through_queue() {
+        let server = MockHttp::start(vec![response("204 No Content", "", "")]).await;
+
+        let client = fast_client(&server.url, DiscordConfig::default()).await;
+        client.execute(&sample_tx()).await.unwrap();
+        client.flush(Duration::from_secs(5)).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/");
+        assert_eq!(requests[0].header("Content-Type"), Some("application/json"));
+        assert_eq!(requests[0].json()["embeds"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_error_fails() {
+        let server = MockHttp::start(vec![response("400 Bad Request", "", r#"{"code": 50006}"#)]).await;
+
+        let client = fast_client(&server.url, DiscordConfig::default()).await;
+        let batch = [sample_tx()];
+        let result = client.send_batch(&batch, &client.embeds(&batch)).await;
+
+        assert!(result.is_err());
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_alerts_batched_into_one_message() {
+        let server = MockHttp::start(vec![response("204 No Content", "", "")]).await;
+
+        let config = DiscordConfig {
+            batch_window: Duration::from_millis(100),
+            ..DiscordConfig::default()
+        };
+        let client = fast_client(&server.url, config).await;
+        for _ in 0..3 {
+            client.execute(&sample_tx()).await.unwrap();
+        }
+        client.flush(Duration::from_secs(5)).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].json()["embeds"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_batches_split_at_discord_embed_size_limit() {
+        let server = MockHttp::start(vec![response("204 No Content", "", ""); MAX_EMBEDS_PER_MESSAGE]).await;
+        let client = fast_client(&server.url, DiscordConfig::default()).await;
+
+        let batch: Vec<_> = (0..MAX_EMBEDS_PER_MESSAGE as u8)
+            .map(|i| TargetTransaction {
+                tx_hash: alloy::primitives::TxHash::from([i; 32]),
+                path: (0..6).map(|t| Address::from([0x30 + t; 20])).collect(),
+                ..sample_tx()
+            })
+            .collect();
+        let full_size: usize = client.embeds(&batch).iter().map(embed_chars).sum();
+        assert!(full_size > MAX_EMBED_CHARS_PER_MESSAGE, "fixture must exceed one message: {}", full_size);
+
+        client.deliver(&batch).await;
+
+        let requests = server.requests();
+        assert!(requests.len() > 1);
+        let mut delivered = 0;
+        for request in &requests {
+            let embeds = request.json()["embeds"].as_array().unwrap().clone();
+            assert!(embeds.iter().map(embed_chars).sum::<usize>() <= MAX_EMBED_CHARS_PER_MESSAGE);
+            delivered += embeds.len();
+        }
+        assert_eq!(delivered, MAX_EMBEDS_PER_MESSAGE);
+    }
+
+    #[tokio::test]
+    async fn test_digest_mode_skips_realtime_alerts() {
+        let config = DiscordConfig {
+            mode: AlertMode::Digest,
+            ..DiscordConfig::default()
+        };
+        let client = fast_client("http://127.0.0.1:9/unused", config).await;
+        client.execute(&sample_tx()).await.unwrap();
+        client.execute(&sample_tx()).await.unwrap();
+
+        assert_eq!(client.queue.depth(), 0);
+        assert_eq!(client.digest.lock().unwrap().total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_flush_sends_pending_digest_and_keeps_failed_ones() {
+        let server = MockHttp::start(vec![
+            response("400 Bad Request", "", "oops"),
+            response("204 No Content", "", ""),
+        ])
+        .await;
+        let config = DiscordConfig {
+            mode: AlertMode::Digest,
+            ..DiscordConfig::default()
+        };
+        let client = fast_client(&server.url, config).await;
+        client.execute(&sample_tx()).await.unwrap();
+        client.execute(&sample_tx()).await.unwrap();
+
+        assert!(client.flush(Duration::from_secs(5)).await.is_err());
+        assert_eq!(client.digest.lock().unwrap().total, 2, "a failed digest is kept");
+
+        client.execute(&sample_tx()).await.unwrap();
+        client.flush(Duration::from_secs(5)).await.unwrap();
+        assert!(client.digest.lock().unwrap().is_empty());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        let description = requests[1].json()["embeds"][0]["description"].as_str().unwrap().to_string();
+        assert!(description.starts_with("**3** transactions"), "{}", description);
+    }
+
+    #[test]
+    fn test_embed_links_use_chain_explorer() {
+        let mut tx = sample_tx();
+        tx.path = vec![Address::from([0x22; 20])];
+
+        let formatter = Formatter::new(Explorer::for_chain(chain::MAINNET), DiscordConfig::default().formatter.template);
+        let view = formatter.view(&tx);
+        let embed = build_embed(&view, formatter.render(&view, &Markdown));
+        let text = embed.to_string();
+        assert!(text.contains(&format!("https://etherscan.io/tx/0x{}", "11".repeat(32))));
+        assert!(text.contains(&format!("https://etherscan.io/address/{:#x}", Address::from([0x44; 20]))));
+        assert!(text.contains(&format!("https://etherscan.io/token/0x{}", "22".repeat(20))));
+
+        assert_eq!(embed["title"], "transaction detected");
+
+        let formatter = DiscordConfig::default().formatter;
+        let view = formatter.view(&tx);
+        let unlinked = build_embed(&view, formatter.render(&view, &Markdown)).to_string();
+        assert!(!unlinked.contains("https://"));
+
+        tx.block_number = Some(42);
+        let view = formatter.view(&tx);
+        let mined = build_embed(&view, formatter.render(&view, &Markdown));
+        assert_eq!(mined["title"], "transaction mined");
+        assert!(mined["description"].as_str().unwrap().contains("mined in block 42"));
+    }
+
+    #[test]
+    fn test_digest_embed_summarizes_window() {
+        let mut digest = Digest::default();
+        digest.record(&sample_tx(), 5);
+        digest.record(&sample_tx(), 5);
+
+        let embed = build_digest_embed(&digest, Duration::from_secs(300), &DiscordConfig::default().formatter);
+        let text = embed.to_string();
+        assert!(text.contains("swapExactETHForTokens"));
+        assert!(text.contains("2.0000"), "total value should be summed: {}", text);
+    }
 }
@@ -0,0 +1,517 @@
+use crate::action::{SniperAction, TargetTransaction};
+use crate::filter::Filter;
+use crate::format::{self, DetectionPayload};
+use crate::metrics::metrics;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Role, WebSocketConfig};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+pub const DEFAULT_CLIENT_BUFFER: usize = 1024;
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const MAX_CLIENT_MESSAGE: usize = 16 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    pub addr: String,
+    pub client_buffer: usize,
+    pub chain_id: Option<u64>,
+}
+
+impl FeedConfig {
+    pub fn from_env(chain_id: Option<u64>) -> Option<Self> {
+        let addr = std::env::var("FEED_ADDR").ok().filter(|a| !a.is_empty())?;
+
+        Some(Self {
+            addr,
+            client_buffer: std::env::var("FEED_CLIENT_BUFFER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_CLIENT_BUFFER),
+            chain_id,
+        })
+    }
+}
+
+struct Detection {
+    tx: TargetTransaction,
+    json: String,
+}
+
+#[derive(Clone)]
+pub struct FeedServer {
+    sender: broadcast::Sender<Arc<Detection>>,
+    chain_id: Option<u64>,
+    local_addr: SocketAddr,
+}
+
+impl FeedServer {
+    pub async fn bind(config: FeedConfig) -> Result<Self> {
+        let listener = TcpListener::bind(&config.addr)
+            .await
+            .with_context(|| format!("failed to bind feed server on {}", config.addr))?;
+        let local_addr = listener.local_addr()?;
+
+        let (sender, _) = broadcast::channel(config.client_buffer.max(1));
+        let server = Self {
+            sender,
+            chain_id: config.chain_id,
+            local_addr,
+        };
+
+        tokio::spawn(server.clone().accept_loop(listener));
+
+        Ok(server)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    async fn accept_loop(self, listener: TcpListener) {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("feed accept failed: {}", e);
+                    continue;
+                }
+            };
+
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle(stream).await {
+                    tracing::debug!("feed client disconnected: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn handle(self, mut stream: TcpStream) -> Result<()> {
+        let request = read_request_head(&mut stream).await?;
+
+        let target = request.split_whitespace().nth(1).unwrap_or("");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let websocket = header(&request, "upgrade").is_some_and(|v| v.to_ascii_lowercase().contains("websocket"));
+
+        let source = query_param(query, "filter").unwrap_or_default();
+        let filter = match Filter::parse(&source) {
+            Ok(filter) => filter,
+            Err(e) => return respond(&mut stream, "400 Bad Request", &format!("invalid filter: {}\n", e)).await,
+        };
+
+        match path {
+            "/ws" if websocket => match header(&request, "sec-websocket-key") {
+                Some(key) => {
+                    let accept = derive_accept_key(key.as_bytes());
+                    self.serve_websocket(stream, &accept, filter).await
+                }
+                None => respond(&mut stream, "400 Bad Request", "missing Sec-WebSocket-Key\n").await,
+            },
+            "/events" => self.serve_events(stream, filter).await,
+            _ => respond(&mut stream, "404 Not Found", "").await,
+        }
+    }
+
+    async fn serve_events(&self, mut stream: TcpStream, filter: Filter) -> Result<()> {
+        let mut detections = self.subscribe();
+        let _client = ClientGuard::new();
+
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n")
+            .await?;
+        send_event(&mut stream, "subscribed", &subscribed(&filter)).await?;
+
+        let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+        keepalive.tick().await;
+
+        loop {
+            tokio::select! {
+                detection = detections.recv() => match detection {
+                    Ok(detection) => {
+                        if filter.matches(&detection.tx) {
+                            send_event(&mut stream, format::event_name(&detection.tx), &detection.json).await?;
+                        }
+                    }
+                    Err(RecvError::Lagged(dropped)) => {
+                        send_event(&mut stream, "lagged", &lagged(dropped)).await?;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+                _ = keepalive.tick() => {
+                    send_with_timeout(stream.write_all(b": keepalive\n\n")).await?;
+                }
+            }
+        }
+    }
+
+    async fn serve_websocket(&self, mut stream: TcpStream, accept: &str, mut filter: Filter) -> Result<()> {
+        let config = WebSocketConfig {
+            max_message_size: Some(MAX_CLIENT_MESSAGE),
+            max_frame_size: Some(MAX_CLIENT_MESSAGE),
+            ..WebSocketConfig::default()
+        };
+        let handshake = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept
+        );
+        send_with_timeout(stream.write_all(handshake.as_bytes())).await?;
+        let mut socket = WebSocketStream::from_raw_socket(stream, Role::Server, Some(config)).await;
+        let mut detections = self.subscribe();
+        let _client = ClientGuard::new();
+
+        send_with_timeout(socket.send(Message::text(subscribed(&filter)))).await?;
+
+        loop {
+            let outgoing = tokio::select! {
+                detection = detections.recv() => match detection {
+                    Ok(detection) if filter.matches(&detection.tx) => Some(detection.json.clone()),
+                    Ok(_) => None,
+                    Err(RecvError::Lagged(dropped)) => Some(lagged(dropped)),
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+                incoming = socket.next() => match incoming {
+                    Some(Ok(Message::Text(source))) => match Filter::parse(&source) {
+                        Ok(updated) => {
+                            filter = updated;
+                            Some(subscribed(&filter))
+                        }
+                        Err(e) => Some(serde_json::json!({ "event": "error", "message": e.to_string() }).to_string()),
+                    },
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => None,
+                    Some(Err(e)) => return Err(e.into()),
+                },
+            };
+
+            if let Some(text) = outgoing {
+                send_with_timeout(socket.send(Message::text(text))).await?;
+            }
+        }
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Arc<Detection>> {
+        self.sender.subscribe()
+    }
+}
+
+struct ClientGuard;
+
+impl ClientGuard {
+    fn new() -> Self {
+        metrics().feed_clients.inc();
+        Self
+    }
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        metrics().feed_clients.dec();
+    }
+}
+
+fn subscribed(filter: &Filter) -> String {
+    serde_json::json!({ "event": "subscribed", "filter": filter.source() }).to_string()
+}
+
+fn lagged(dropped: u64) -> String {
+    metrics().feed_dropped.add(dropped);
+    tracing::debug!("feed client lagging - skipped {} detections", dropped);
+    serde_json::json!({ "event": "lagged", "dropped": dropped }).to_string()
+}
+
+async fn send_event(stream: &mut TcpStream, event: &str, data: &str) -> Result<()> {
+    let frame = format!("event: {}\ndata: {}\n\n", event, data);
+    send_with_timeout(stream.write_all(frame.as_bytes())).await
+}
+
+async fn send_with_timeout<E>(send: impl std::future::Future<Output = Result<(), E>>) -> Result<()>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    match tokio::time::timeout(SEND_TIMEOUT, send).await {
+        Ok(result) => Ok(result?),
+        Err(_) => {
+            metrics().feed_slow_disconnects.inc();
+            anyhow::bail!("client did not accept data within {:?}", SEND_TIMEOUT)
+        }
+    }
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    let read = async {
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            anyhow::ensure!(head.len() < MAX_REQUEST_HEAD, "request head larger than {} bytes", MAX_REQUEST_HEAD);
+            let n = stream.read(&mut buf).await?;
+            anyhow::ensure!(n > 0, "client closed the connection before sending a request");
+            head.extend_from_slice(&buf[..n]);
+        }
+        Ok(())
+    };
+
+    tokio::time::timeout(REQUEST_TIMEOUT, read)
+        .await
+        .map_err(|_| anyhow::anyhow!("no request received within {:?}", REQUEST_TIMEOUT))??;
+
+    Ok(String::from_utf8_lossy(&head).to_string())
+}
+
+fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[async_trait]
+impl SniperAction for FeedServer {
+    async fn execute(&self, tx: &TargetTransaction) -> Result<()> {
+        if self.sender.receiver_count() == 0 {
+            return Ok(());
+        }
+
+        let json = serde_json::to_string(&DetectionPayload::new(tx, self.chain_id))?;
+        let _ = self.sender.send(Arc::new(Detection { tx: tx.clone(), json }));
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "feed"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{target_tx, ONE_ETH};
+    use alloy::primitives::U256;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    fn tx(id: u8, method: &str) -> TargetTransaction {
+        TargetTransaction {
+            value: U256::from(id as u64) * U256::from(ONE_ETH),
+            method: method.to_string(),
+            ..target_tx(id)
+        }
+    }
+
+    async fn server(client_buffer: usize) -> FeedServer {
+        FeedServer::bind(FeedConfig {
+            addr: "127.0.0.1:0".to_string(),
+            client_buffer,
+            chain_id: Some(1),
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn wait_for_clients(server: &FeedServer, clients: usize) {
+        for _ in 0..100 {
+            if server.sender.receiver_count() >= clients {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("feed clients did not subscribe");
+    }
+
+    #[test]
+    fn test_query_param_decoding() {
+        assert_eq!(
+            query_param("x=1&filter=value%20%3E%3D%201+%26%26+method%3D%3Dmulticall", "filter").as_deref(),
+            Some("value >= 1 && method==multicall")
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert!(query_param("x=1", "filter").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_filter_and_resubscribe() {
+        let server = server(16).await;
+        let url = format!("ws://{}/ws?filter=value%20%3E%3D%202", server.local_addr());
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        let text = |message: Message| serde_json::from_str::<serde_json::Value>(message.to_text().unwrap()).unwrap();
+
+        let hello = text(socket.next().await.unwrap().unwrap());
+        assert_eq!(hello, serde_json::json!({ "event": "subscribed", "filter": "value >= 2" }));
+
+        wait_for_clients(&server, 1).await;
+        server.execute(&tx(1, "multicall")).await.unwrap();
+        server.execute(&tx(3, "multicall")).await.unwrap();
+
+        let detection = text(socket.next().await.unwrap().unwrap());
+        assert_eq!(detection["tx_hash"], format!("0x{}", "03".repeat(32)));
+        assert_eq!(detection["chain_id"], 1);
+
+        socket.send(Message::text("method >")).await.unwrap();
+        assert_eq!(text(socket.next().await.unwrap().unwrap())["event"], "error");
+
+        socket.send(Message::text("method == exactInput")).await.unwrap();
+        assert_eq!(text(socket.next().await.unwrap().unwrap())["filter"], "method == exactInput");
+
+        server.execute(&tx(5, "multicall")).await.unwrap();
+        server.execute(&tx(6, "exactInput")).await.unwrap();
+        assert_eq!(text(socket.next().await.unwrap().unwrap())["method"], "exactInput");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_websocket_message_closes_client() {
+        let server = server(16).await;
+        let url = format!("ws://{}/ws", server.local_addr());
+        let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        socket.next().await.unwrap().unwrap();
+
+        socket.send(Message::text("(".repeat(MAX_CLIENT_MESSAGE + 1))).await.unwrap();
+        match socket.next().await {
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {}
+            Some(Ok(message)) => panic!("unexpected reply to an oversized filter: {:?}", message),
+        }
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        assert!(socket.next().await.unwrap().unwrap().to_text().unwrap().contains("subscribed"));
+    }
+
+    #[tokio::test]
+    async fn test_sse_stream_reports_lag() {
+        let server = server(2).await;
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        stream
+            .write_all(b"GET /events?filter=method+%3D%3D+multicall HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut lines = BufReader::new(stream).lines();
+
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "HTTP/1.1 200 OK");
+        while !lines.next_line().await.unwrap().unwrap().is_empty() {}
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "event: subscribed");
+        lines.next_line().await.unwrap();
+        lines.next_line().await.unwrap();
+
+        wait_for_clients(&server, 1).await;
+        for id in 1..=5 {
+            server.execute(&tx(id, "multicall")).await.unwrap();
+        }
+
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "event: lagged");
+        let data = lines.next_line().await.unwrap().unwrap();
+        let notice: serde_json::Value = serde_json::from_str(data.strip_prefix("data: ").unwrap()).unwrap();
+        assert_eq!(notice, serde_json::json!({ "event": "lagged", "dropped": 3 }));
+        lines.next_line().await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "event: transaction_detected");
+        let data = lines.next_line().await.unwrap().unwrap();
+        assert!(data.contains(&format!("0x{}", "04".repeat(32))));
+    }
+
+    #[tokio::test]
+    async fn test_request_split_across_writes() {
+        let server = server(16).await;
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        stream.write_all(b"GET /events?filter=method").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stream.write_all(b"+%3D%3D+multicall HTTP/1.1\r\nHost: local").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stream.write_all(b"host\r\n\r\n").await.unwrap();
+
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "HTTP/1.1 200 OK");
+        while !lines.next_line().await.unwrap().unwrap().is_empty() {}
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "event: subscribed");
+        assert!(lines.next_line().await.unwrap().unwrap().contains("method == multicall"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_and_oversized_requests_are_closed() {
+        let server = server(16).await;
+
+        let mut idle = TcpStream::connect(server.local_addr()).await.unwrap();
+        idle.write_all(b"GET /events HTTP/1.1\r\n").await.unwrap();
+        let started = tokio::time::Instant::now();
+        assert_eq!(idle.read(&mut [0u8; 64]).await.unwrap(), 0);
+        assert!(started.elapsed() >= REQUEST_TIMEOUT);
+
+        let mut oversized = TcpStream::connect(server.local_addr()).await.unwrap();
+        let header = format!("GET /events HTTP/1.1\r\nX-Padding: {}\r\n", "a".repeat(MAX_REQUEST_HEAD));
+        oversized.write_all(header.as_bytes()).await.unwrap();
+        let mut rest = Vec::new();
+        let _ = oversized.read_to_end(&mut rest).await;
+        assert!(rest.is_empty(), "no response to an oversized request");
+        assert_eq!(server.sender.receiver_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_filter_rejected() {
+        let server = server(16).await;
+        let response = reqwest::get(format!("http://{}/events?filter=gas%3E1", server.local_addr()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        assert!(response.text().await.unwrap().contains("unknown field 'gas'"));
+
+        let status = reqwest::get(format!("http://{}/other", server.local_addr())).await.unwrap().status();
+        assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,446 @@
+use crate::action::TargetTransaction;
+use alloy::primitives::{Address, U256};
+use thiserror::Error;
+
+pub const FIELDS: &[&str] = &["value", "value_wei", "method", "from", "to", "token"];
+pub const MAX_FILTER_LEN: usize = 4096;
+pub const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FilterError {
+    #[error("unexpected end of filter")]
+    UnexpectedEnd,
+    #[error("unexpected '{0}'")]
+    Unexpected(String),
+    #[error("unterminated string")]
+    UnterminatedString,
+    #[error("unknown field '{0}' (expected one of {fields})", fields = FIELDS.join(", "))]
+    UnknownField(String),
+    #[error("operator '{op}' is not supported for '{field}'")]
+    UnsupportedOperator { field: String, op: &'static str },
+    #[error("invalid value '{value}' for '{field}'")]
+    InvalidValue { field: String, value: String },
+    #[error("filter is longer than {MAX_FILTER_LEN} characters")]
+    TooLong,
+    #[error("filter is nested deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+        }
+    }
+
+    fn compare<T: Ord>(self, left: T, right: T) -> bool {
+        match self {
+            Op::Eq => left == right,
+            Op::Ne => left != right,
+            Op::Gt => left > right,
+            Op::Ge => left >= right,
+            Op::Lt => left < right,
+            Op::Le => left <= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(w) | Token::Str(w) => w.clone(),
+            Token::Op(op) => op.symbol().to_string(),
+            Token::And => "&&".to_string(),
+            Token::Or => "||".to_string(),
+            Token::Not => "!".to_string(),
+            Token::Open => "(".to_string(),
+            Token::Close => ")".to_string(),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let two: String = chars.clone().take(2).collect();
+        let token = match two.as_str() {
+            "&&" => Some(Token::And),
+            "||" => Some(Token::Or),
+            "==" => Some(Token::Op(Op::Eq)),
+            "!=" => Some(Token::Op(Op::Ne)),
+            ">=" => Some(Token::Op(Op::Ge)),
+            "<=" => Some(Token::Op(Op::Le)),
+            _ => None,
+        };
+        if let Some(token) = token {
+            chars.next();
+            chars.next();
+            tokens.push(token);
+            continue;
+        }
+
+        match c {
+            '(' | ')' | '!' | '>' | '<' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    '!' => Token::Not,
+                    '>' => Token::Op(Op::Gt),
+                    _ => Token::Op(Op::Lt),
+                });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some(ch) => text.push(ch),
+                        None => return Err(FilterError::UnterminatedString),
+                    }
+                }
+                tokens.push(Token::Str(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || "()!=<>&|\"'".contains(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                if word.is_empty() {
+                    return Err(FilterError::Unexpected(c.to_string()));
+                }
+                tokens.push(match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Value(Op, U256),
+    Method(Op, String),
+    From(Op, Address),
+    To(Op, Address),
+    Token(Op, Address),
+}
+
+impl Condition {
+    fn parse(field: &str, op: Op, value: &str) -> Result<Self, FilterError> {
+        let invalid = || FilterError::InvalidValue {
+            field: field.to_string(),
+            value: value.to_string(),
+        };
+        let equality_only = |op: Op| match op {
+            Op::Eq | Op::Ne => Ok(op),
+            _ => Err(FilterError::UnsupportedOperator {
+                field: field.to_string(),
+                op: op.symbol(),
+            }),
+        };
+
+        match field {
+            "value" => Ok(Condition::Value(op, parse_eth(value).ok_or_else(invalid)?)),
+            "value_wei" => Ok(Condition::Value(op, value.parse().map_err(|_| invalid())?)),
+            "method" => Ok(Condition::Method(equality_only(op)?, value.to_string())),
+            "from" => Ok(Condition::From(equality_only(op)?, value.parse().map_err(|_| invalid())?)),
+            "to" => Ok(Condition::To(equality_only(op)?, value.parse().map_err(|_| invalid())?)),
+            "token" => Ok(Condition::Token(equality_only(op)?, value.parse().map_err(|_| invalid())?)),
+            _ => Err(FilterError::UnknownField(field.to_string())),
+        }
+    }
+
+    fn matches(&self, tx: &TargetTransaction) -> bool {
+        match self {
+            Condition::Value(op, wei) => op.compare(tx.value, *wei),
+            Condition::Method(op, method) => op.compare(tx.method.eq_ignore_ascii_case(method), true),
+            Condition::From(op, address) => op.compare(tx.from, *address),
+            Condition::To(op, address) => op.compare(tx.to, *address),
+            Condition::Token(op, token) => op.compare(tx.path.contains(token), true),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Condition(Condition),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn matches(&self, tx: &TargetTransaction) -> bool {
+        match self {
+            Expr::Condition(condition) => condition.matches(tx),
+            Expr::Not(inner) => !inner.matches(tx),
+            Expr::And(left, right) => left.matches(tx) && right.matches(tx),
+            Expr::Or(left, right) => left.matches(tx) || right.matches(tx),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Result<Token, FilterError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or(FilterError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, FilterError>) -> Result<T, FilterError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(FilterError::TooDeep);
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        match self.next()? {
+            Token::Not => Ok(Expr::Not(Box::new(self.nested(Self::unary)?))),
+            Token::Open => {
+                let expr = self.nested(Self::or)?;
+                match self.next()? {
+                    Token::Close => Ok(expr),
+                    other => Err(FilterError::Unexpected(other.describe())),
+                }
+            }
+            Token::Word(field) => {
+                let op = match self.next()? {
+                    Token::Op(op) => op,
+                    other => return Err(FilterError::Unexpected(other.describe())),
+                };
+                let value = match self.next()? {
+                    Token::Word(value) | Token::Str(value) => value,
+                    other => return Err(FilterError::Unexpected(other.describe())),
+                };
+                Ok(Expr::Condition(Condition::parse(&field.to_ascii_lowercase(), op, &value)?))
+            }
+            other => Err(FilterError::Unexpected(other.describe())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    source: String,
+    expr: Option<Expr>,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Self, FilterError> {
+        if source.len() > MAX_FILTER_LEN {
+            return Err(FilterError::TooLong);
+        }
+
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut parser = Parser { tokens, pos: 0, depth: 0 };
+        let expr = parser.or()?;
+        if let Some(extra) = parser.tokens.get(parser.pos) {
+            return Err(FilterError::Unexpected(extra.describe()));
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            expr: Some(expr),
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, tx: &TargetTransaction) -> bool {
+        self.expr.as_ref().is_none_or(|expr| expr.matches(tx))
+    }
+}
+
+fn parse_eth(value: &str) -> Option<U256> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if (whole.is_empty() && fraction.is_empty()) || fraction.len() > 18 {
+        return None;
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let whole: U256 = if whole.is_empty() { U256::ZERO } else { whole.parse().ok()? };
+    let fraction: U256 = if fraction.is_empty() {
+        U256::ZERO
+    } else {
+        format!("{:0<18}", fraction).parse().ok()?
+    };
+
+    whole
+        .checked_mul(U256::from(1_000_000_000_000_000_000u128))?
+        .checked_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample_tx;
+    use alloy::primitives::TxHash;
+
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+
+    fn tx(method: &str, value_milli_eth: u64) -> TargetTransaction {
+        TargetTransaction {
+            tx_hash: TxHash::ZERO,
+            value: U256::from(value_milli_eth) * U256::from(1_000_000_000_000_000u64),
+            method: method.to_string(),
+            path: vec![WETH.parse().unwrap()],
+            detected_at: 0,
+            ..sample_tx()
+        }
+    }
+
+    #[test]
+    fn test_parse_eth() {
+        assert_eq!(parse_eth("1"), Some(U256::from(1_000_000_000_000_000_000u128)));
+        assert_eq!(parse_eth("0.5"), Some(U256::from(500_000_000_000_000_000u128)));
+        assert_eq!(parse_eth(".25"), Some(U256::from(250_000_000_000_000_000u128)));
+        assert_eq!(parse_eth("0.000000000000000001"), Some(U256::from(1u64)));
+        assert_eq!(parse_eth("0.0000000000000000001"), None);
+        assert_eq!(parse_eth("1e18"), None);
+        assert_eq!(parse_eth("."), None);
+    }
+
+    #[test]
+    fn test_matching() {
+        let swap = tx("swapExactETHForTokens", 1500);
+        let transfer = tx("Native Transfer", 200);
+
+        let filter = Filter::parse("value >= 1 && method == swapExactETHForTokens").unwrap();
+        assert!(filter.matches(&swap));
+        assert!(!filter.matches(&transfer));
+
+        let filter = Filter::parse("method == 'native transfer' or value > 1.4").unwrap();
+        assert!(filter.matches(&swap));
+        assert!(filter.matches(&transfer));
+
+        let filter = Filter::parse(&format!("token == {} && !(method == \"Native Transfer\")", WETH)).unwrap();
+        assert!(filter.matches(&swap));
+        assert!(!filter.matches(&transfer));
+
+        let filter = Filter::parse(&format!("from != 0x{}", "22".repeat(20))).unwrap();
+        assert!(!filter.matches(&swap));
+
+        assert!(Filter::parse("  ").unwrap().matches(&swap));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let low = tx("exactInput", 100);
+        let filter = Filter::parse("method == multicall && value > 1 || method == exactInput").unwrap();
+        assert!(filter.matches(&low));
+
+        let filter = Filter::parse("method == multicall && (value > 1 || method == exactInput)").unwrap();
+        assert!(!filter.matches(&low));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Filter::parse("gas > 1").unwrap_err(), FilterError::UnknownField("gas".to_string()));
+        assert_eq!(
+            Filter::parse("method > swap").unwrap_err(),
+            FilterError::UnsupportedOperator { field: "method".to_string(), op: ">" }
+        );
+        assert_eq!(
+            Filter::parse("to == 0x12").unwrap_err(),
+            FilterError::InvalidValue { field: "to".to_string(), value: "0x12".to_string() }
+        );
+        assert_eq!(Filter::parse("value >").unwrap_err(), FilterError::UnexpectedEnd);
+        assert_eq!(Filter::parse("(value > 1").unwrap_err(), FilterError::UnexpectedEnd);
+        assert_eq!(Filter::parse("value > 1 value").unwrap_err(), FilterError::Unexpected("value".to_string()));
+        assert_eq!(Filter::parse("method == 'swap").unwrap_err(), FilterError::UnterminatedString);
+        assert_eq!(Filter::parse(&"(".repeat(200_000)).unwrap_err(), FilterError::TooLong);
+        assert_eq!(Filter::parse(&"(".repeat(MAX_DEPTH + 1)).unwrap_err(), FilterError::TooDeep);
+        assert_eq!(Filter::parse(&"!".repeat(MAX_DEPTH + 1)).unwrap_err(), FilterError::TooDeep);
+
+        let nested = format!("{}value > 1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert!(Filter::parse(&nested).is_ok());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::action::TargetTransaction;
+use crate::chain;
+use crate::latency::Timings;
+use alloy::primitives::{Address, TxHash, B256, U256};
+use serde_json::{json, Value};
+
+pub const ONE_ETH: u128 = 1_000_000_000_000_000_000;
+
+pub fn tx_hash(n: u64) -> B256 {
+    B256::left_padding_from(&n.to_be_bytes())
+}
+
+pub fn rpc_tx(n: u64, value_wei: u64) -> Value {
+    rpc_tx_on(chain::MAINNET, n, value_wei)
+}
+
+pub fn rpc_tx_on(chain_id: u64, n: u64, value_wei: u64) -> Value {
+    json!({
+        "hash": tx_hash(n),
+        "nonce": format!("{:#x}", n),
+        "blockHash": null,
+        "blockNumber": null,
+        "transactionIndex": null,
+        "from": "0x1111111111111111111111111111111111111111",
+        "to": "0x2222222222222222222222222222222222222222",
+        "value": format!("{:#x}", value_wei),
+        "gasPrice": "0x3b9aca00",
+        "gas": "0x5208",
+        "input": "0x",
+        "v": format!("{:#x}", chain_id * 2 + 35),
+        "r": "0x1",
+        "s": "0x1",
+        "chainId": format!("{:#x}", chain_id),
+        "type": "0x0"
+    })
+}
+
+pub fn target_tx(id: u8) -> TargetTransaction {
+    TargetTransaction {
+        tx_hash: TxHash::from([id; 32]),
+        from: Address::ZERO,
+        value: U256::ZERO,
+        method: "Native Transfer".to_string(),
+        amount_out_min: U256::ZERO,
+        path: vec![],
+        to: Address::ZERO,
+        deadline: U256::ZERO,
+        detected_at: 0,
+        block_number: None,
+        timings: Timings::default(),
+    }
+}
+
+pub fn sample_tx() -> TargetTransaction {
+    TargetTransaction {
+        from: Address::from([0x22; 20]),
+        value: U256::from(ONE_ETH),
+        method: "swapExactETHForTokens".to_string(),
+        path: vec![Address::from([0x33; 20])],
+        to: Address::from([0x44; 20]),
+        detected_at: 1_700_000_000_000,
+        ..target_tx(0x11)
+    }
+}
@@ -0,0 +1,316 @@
+use crate::action::TargetTransaction;
+use crate::chain::{self, Explorer};
+use crate::template::{self, Context, Markup, Template, Value};
+use alloy::primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+pub const VARIABLES: &[&str] = &[
+    "value",
+    "value_wei",
+    "method",
+    "tx",
+    "from",
+    "to",
+    "path",
+    "detected_at",
+    "detected_time",
+    "status",
+    "block",
+];
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectionPayload {
+    pub schema_version: u32,
+    pub event: String,
+    pub chain_id: Option<u64>,
+    pub tx_hash: String,
+    pub from: String,
+    pub to: String,
+    pub value_wei: String,
+    pub method: String,
+    pub path: Vec<String>,
+    pub amount_out_min: String,
+    pub deadline: String,
+    pub detected_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+}
+
+impl DetectionPayload {
+    pub fn new(tx: &TargetTransaction, chain_id: Option<u64>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            event: event_name(tx).to_string(),
+            chain_id,
+            tx_hash: format!("{:?}", tx.tx_hash),
+            from: format!("{:#x}", tx.from),
+            to: format!("{:#x}", tx.to),
+            value_wei: tx.value.to_string(),
+            method: tx.method.clone(),
+            path: tx.path.iter().map(|a| format!("{:#x}", a)).collect(),
+            amount_out_min: tx.amount_out_min.to_string(),
+            deadline: tx.deadline.to_string(),
+            detected_at: tx.detected_at,
+            block_number: tx.block_number,
+        }
+    }
+}
+
+pub fn event_name(tx: &TargetTransaction) -> &'static str {
+    match tx.block_number {
+        Some(_) => "transaction_mined",
+        None => "transaction_detected",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub short: String,
+    pub full: String,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertView {
+    pub value_eth: String,
+    pub value_wei: String,
+    pub method: String,
+    pub tx: Link,
+    pub from: Link,
+    pub to: Link,
+    pub path: Vec<Link>,
+    pub detected_at_secs: u64,
+    pub block_number: Option<u64>,
+    pub event: &'static str,
+}
+
+impl AlertView {
+    pub fn new(tx: &TargetTransaction, explorer: &Explorer) -> Self {
+        Self {
+            value_eth: format_eth(tx.value),
+            value_wei: tx.value.to_string(),
+            method: tx.method.clone(),
+            tx: Link {
+                short: chain::short_hash(&tx.tx_hash),
+                full: format!("{:?}", tx.tx_hash),
+                url: explorer.tx_url(&tx.tx_hash),
+            },
+            from: address_link(&tx.from, explorer),
+            to: address_link(&tx.to, explorer),
+            path: tx.path.iter().map(|t| token_link(t, explorer)).collect(),
+            detected_at_secs: tx.detected_at / 1000,
+            block_number: tx.block_number,
+            event: event_name(tx),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        self.event.replace('_', " ")
+    }
+
+    pub fn context(&self) -> Context {
+        let detected_time = chrono::DateTime::from_timestamp(self.detected_at_secs as i64, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default();
+
+        let mut context = Context::new();
+        context.insert("value", Value::Text(self.value_eth.clone()));
+        context.insert("value_wei", Value::Text(self.value_wei.clone()));
+        context.insert("method", Value::Text(self.method.clone()));
+        context.insert("tx", Value::Link(self.tx.clone()));
+        context.insert("from", Value::Link(self.from.clone()));
+        context.insert("to", Value::Link(self.to.clone()));
+        context.insert("path", Value::List(self.path.clone()));
+        context.insert("detected_at", Value::Text(self.detected_at_secs.to_string()));
+        context.insert("detected_time", Value::Text(detected_time));
+        context.insert(
+            "status",
+            Value::Text(if self.block_number.is_some() { "mined" } else { "pending" }.to_string()),
+        );
+        context.insert(
+            "block",
+            Value::Text(self.block_number.map(|n| n.to_string()).unwrap_or_default()),
+        );
+        context
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    pub explorer: Explorer,
+    pub template: Template,
+}
+
+impl Formatter {
+    pub fn new(explorer: Explorer, template: Template) -> Self {
+        Self { explorer, template }
+    }
+
+    pub fn load(name: &str, default: &str, explorer: &Explorer) -> anyhow::Result<Self> {
+        Ok(Self::new(explorer.clone(), template::load(name, default, VARIABLES)?))
+    }
+
+    pub fn with_default(default: &str) -> Self {
+        Self::new(
+            Explorer::default(),
+            Template::parse(default).expect("built-in template should parse"),
+        )
+    }
+
+    pub fn view(&self, tx: &TargetTransaction) -> AlertView {
+        AlertView::new(tx, &self.explorer)
+    }
+
+    pub fn render(&self, view: &AlertView, markup: &dyn Markup) -> String {
+        self.template.render(&view.context(), markup)
+    }
+}
+
+pub fn address_link(address: &Address, explorer: &Explorer) -> Link {
+    Link {
+        short: chain::short_address(address),
+        full: format!("{:#x}", address),
+        url: explorer.address_url(address),
+    }
+}
+
+pub fn token_link(token: &Address, explorer: &Explorer) -> Link {
+    Link {
+        short: chain::short_address(token),
+        full: format!("{:#x}", token),
+        url: explorer.token_url(token),
+    }
+}
+
+pub fn format_eth(wei: U256) -> String {
+    let eth_divisor = U256::from(1_000_000_000_000_000_000u128);
+    let eth_whole = wei / eth_divisor;
+    let eth_fraction = wei % eth_divisor;
+
+    let fraction_scaled = eth_fraction * U256::from(10000u128) / eth_divisor;
+
+    format!("{}.{:04}", eth_whole, fraction_scaled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    struct Plain;
+
+    impl Markup for Plain {
+        fn escape(&self, text: &str) -> String {
+            text.to_string()
+        }
+
+        fn code(&self, text: &str) -> String {
+            text.to_string()
+        }
+
+        fn link(&self, link: &Link) -> String {
+            link.short.clone()
+        }
+    }
+
+    fn sample_tx() -> TargetTransaction {
+        TargetTransaction {
+            value: U256::from(2_500_000_000_000_000_000u128),
+            detected_at: 1_700_000_000_500,
+            ..fixtures::sample_tx()
+        }
+    }
+
+    #[test]
+    fn test_format_eth() {
+        let one_eth = U256::from(1_000_000_000_000_000_000u128);
+        assert_eq!(format_eth(one_eth), "1.0000");
+
+        let half_eth = U256::from(500_000_000_000_000_000u128);
+        assert_eq!(format_eth(half_eth), "0.5000");
+
+        let ten_quarter_eth = U256::from(10_250_000_000_000_000_000u128);
+        assert_eq!(format_eth(ten_quarter_eth), "10.2500");
+
+        let small_eth = U256::from(100_000_000_000_000u128);
+        assert_eq!(format_eth(small_eth), "0.0001");
+    }
+
+    #[test]
+    fn test_alert_view_from_transaction() {
+        let tx = sample_tx();
+
+        let view = AlertView::new(&tx, &Explorer::for_chain(chain::SEPOLIA));
+        assert_eq!(view.value_eth, "2.5000");
+        assert_eq!(view.tx.short, "0x11111111...11111111");
+        assert_eq!(view.from.full, format!("0x{}", "22".repeat(20)));
+        assert_eq!(view.to.url.as_deref(), Some(&*format!("https://sepolia.etherscan.io/address/0x{}", "44".repeat(20))));
+        assert_eq!(view.path[0].url.as_deref(), Some(&*format!("https://sepolia.etherscan.io/token/0x{}", "33".repeat(20))));
+        assert_eq!(view.detected_at_secs, 1_700_000_000);
+        assert_eq!(view.block_number, None);
+
+        let unlinked = AlertView::new(&tx, &Explorer::default());
+        assert!(unlinked.tx.url.is_none());
+    }
+
+    #[test]
+    fn test_builtin_templates_are_valid() {
+        for source in [
+            crate::action::DEFAULT_CONSOLE_TEMPLATE,
+            crate::discord::DEFAULT_TEMPLATE,
+            crate::telegram::DEFAULT_TEMPLATE,
+            crate::slack::DEFAULT_TEMPLATE,
+        ] {
+            Template::parse(source).unwrap().check(VARIABLES).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_builtin_templates_flag_mined_transactions() {
+        let mined = TargetTransaction {
+            block_number: Some(19_000_000),
+            ..sample_tx()
+        };
+        for source in [
+            crate::action::DEFAULT_CONSOLE_TEMPLATE,
+            crate::discord::DEFAULT_TEMPLATE,
+            crate::telegram::DEFAULT_TEMPLATE,
+            crate::slack::DEFAULT_TEMPLATE,
+        ] {
+            let formatter = Formatter::with_default(source);
+            let rendered = formatter.render(&formatter.view(&mined), &Plain);
+            assert!(rendered.contains("mined") && rendered.contains("19000000"), "{}", rendered);
+
+            let pending = formatter.render(&formatter.view(&sample_tx()), &Plain);
+            assert!(pending.contains("pending") && !pending.contains("block"), "{}", pending);
+        }
+
+        assert_eq!(AlertView::new(&mined, &Explorer::default()).title(), "transaction mined");
+        assert_eq!(AlertView::new(&sample_tx(), &Explorer::default()).title(), "transaction detected");
+    }
+
+    #[test]
+    fn test_every_variable_is_in_context() {
+        let context = AlertView::new(&sample_tx(), &Explorer::default()).context();
+        for name in VARIABLES {
+            assert!(context.contains_key(name), "missing variable {}", name);
+        }
+        assert_eq!(context.len(), VARIABLES.len());
+
+        let formatter = Formatter::with_default("{{value}} eth {{method}} at {{detected_time}}");
+        assert_eq!(
+            formatter.render(&formatter.view(&sample_tx()), &Plain),
+            "2.5000 eth swapExactETHForTokens at 2023-11-14 22:13:20 UTC"
+        );
+
+        let status = Formatter::with_default("{{status}}{{#if block}} in block {{block}}{{/if}}");
+        assert_eq!(status.render(&status.view(&sample_tx()), &Plain), "pending");
+        let mined = TargetTransaction {
+            block_number: Some(19_000_000),
+            ..sample_tx()
+        };
+        assert_eq!(status.render(&status.view(&mined), &Plain), "mined in block 19000000");
+    }
+}
@@ -1,10 +1,368 @@
-use alloy::providers::{ProviderBuilder, RootProvider, WsConnect};
-use alloy::pubsub::PubSubFrontend;
-use anyhow::Result;
+use crate::decoder::{DecodedTx, Decoder};
+use crate::latency::Timings;
+use alloy::consensus::Transaction;
+use alloy::primitives::{B256, U256};
+use alloy::providers::{IpcConnect, Provider, ProviderBuilder, RootProvider, WsConnect};
+use alloy::pubsub::Subscription;
+use alloy::rpc::types::Transaction as RpcTransaction;
+use alloy::transports::{BoxTransport, RpcError, TransportError, TransportErrorKind};
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
 
-pub async fn establish_connection(url: &str) -> Result<RootProvider<PubSubFrontend>> {
-    let ws = WsConnect::new(url);
-    let provider = ProviderBuilder::new().on_ws(ws).await?;
+const JSONRPC_METHOD_NOT_FOUND: i64 = -32601;
+const JSONRPC_LIMIT_EXCEEDED: i64 = -32005;
+const HTTP_TOO_MANY_REQUESTS: u16 = 429;
 
-    Ok(provider)
+const DEFAULT_POLL_INTERVAL_MS: u64 = 500;
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const MAX_RATE_LIMIT_DELAY: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("subscription lagged by {0} messages")]
+    Lagged(u64),
+    #[error("subscription not found (provider may not support eth_subscribe)")]
+    SubscriptionNotFound,
+    #[error("connection closed (network issue or provider restart)")]
+    ConnectionClosed,
+    #[error("rate limit exceeded (too many requests)")]
+    RateLimited,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl IngestError {
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        match self {
+            IngestError::Lagged(_) => Duration::ZERO,
+            IngestError::SubscriptionNotFound => Duration::from_secs(1),
+            IngestError::ConnectionClosed | IngestError::Other(_) => {
+                backoff(Duration::from_secs(1), attempt, MAX_RETRY_DELAY)
+            }
+            IngestError::RateLimited => {
+                backoff(Duration::from_secs(5), attempt, MAX_RATE_LIMIT_DELAY)
+            }
+        }
+    }
+}
+
+fn backoff(base: Duration, attempt: u32, max: Duration) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempt)).min(max)
+}
+
+impl From<TransportError> for IngestError {
+    fn from(e: TransportError) -> Self {
+        match e {
+            RpcError::ErrorResp(payload) => {
+                let message = payload.message.to_lowercase();
+                if message.contains("subscription not found")
+                    || message.contains("filter not found")
+                    || payload.code == JSONRPC_METHOD_NOT_FOUND
+                {
+                    IngestError::SubscriptionNotFound
+                } else if payload.code == JSONRPC_LIMIT_EXCEEDED
+                    || payload.code == i64::from(HTTP_TOO_MANY_REQUESTS)
+                    || message.contains("rate limit")
+                {
+                    IngestError::RateLimited
+                } else {
+                    IngestError::Other(payload.to_string())
+                }
+            }
+            RpcError::Transport(TransportErrorKind::BackendGone) => IngestError::ConnectionClosed,
+            RpcError::Transport(TransportErrorKind::PubsubUnavailable) => {
+                IngestError::SubscriptionNotFound
+            }
+            RpcError::Transport(TransportErrorKind::HttpError(http))
+                if http.status == HTTP_TOO_MANY_REQUESTS =>
+            {
+                IngestError::RateLimited
+            }
+            other => IngestError::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<RecvError> for IngestError {
+    fn from(e: RecvError) -> Self {
+        match e {
+            RecvError::Lagged(n) => IngestError::Lagged(n),
+            RecvError::Closed => IngestError::ConnectionClosed,
+        }
+    }
+}
+
+pub type SniperProvider = RootProvider<BoxTransport>;
+
+#[derive(Debug, Clone)]
+pub struct PendingTx {
+    pub tx: RpcTransaction,
+    pub timings: Timings,
+    pub decoded: Option<DecodedTx>,
+}
+
+impl PendingTx {
+    pub fn new(tx: RpcTransaction, timings: Timings) -> Self {
+        Self { tx, timings, decoded: None }
+    }
+
+    pub fn priority(&mut self, decoder: &dyn Decoder, by_value: bool) -> U256 {
+        if !by_value {
+            return self.tx.inner.value();
+        }
+
+        match decoder.decode(self.tx.inner.input(), self.tx.inner.value()) {
+            Ok(decoded) => {
+                let value = decoded.effective_value;
+                self.decoded = Some(decoded);
+                value
+            }
+            Err(_) => self.tx.inner.value(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportKind {
+    Ws,
+    Ipc,
+    Http,
+}
+
+impl TransportKind {
+    pub fn from_url(url: &str) -> Result<Self> {
+        match url.split_once("://") {
+            Some((scheme, _)) => match scheme.to_lowercase().as_str() {
+                "ws" | "wss" => Ok(TransportKind::Ws),
+                "http" | "https" => Ok(TransportKind::Http),
+                "ipc" | "file" => Ok(TransportKind::Ipc),
+                other => anyhow::bail!("unsupported rpc url scheme: {}", other),
+            },
+            None if url.ends_with(".ipc") || url.starts_with('/') => Ok(TransportKind::Ipc),
+            None => anyhow::bail!("rpc url has no scheme: {}", url),
+        }
+    }
+}
+
+impl fmt::Display for TransportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportKind::Ws => write!(f, "websocket"),
+            TransportKind::Ipc => write!(f, "ipc"),
+            TransportKind::Http => write!(f, "http polling"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Connection {
+    pub provider: SniperProvider,
+    pub kind: TransportKind,
+    pub poll_interval: Duration,
+}
+
+pub enum HashStream {
+    Subscription(Subscription<B256>),
+    Poll {
+        provider: SniperProvider,
+        filter_id: U256,
+        poll_interval: Duration,
+        buffered: VecDeque<B256>,
+    },
+}
+
+impl HashStream {
+    pub async fn open(conn: &Connection) -> Result<Self, IngestError> {
+        match conn.kind {
+            TransportKind::Ws | TransportKind::Ipc => {
+                let sub = conn.provider.subscribe_pending_transactions().await?;
+                Ok(HashStream::Subscription(sub))
+            }
+            TransportKind::Http => {
+                let filter_id = conn.provider.new_pending_transactions_filter(false).await?;
+                Ok(HashStream::Poll {
+                    provider: conn.provider.clone(),
+                    filter_id,
+                    poll_interval: conn.poll_interval,
+                    buffered: VecDeque::new(),
+                })
+            }
+        }
+    }
+
+    pub async fn recv(&mut self) -> Result<B256, IngestError> {
+        match self {
+            HashStream::Subscription(sub) => Ok(sub.recv().await?),
+            HashStream::Poll {
+                provider,
+                filter_id,
+                poll_interval,
+                buffered,
+            } => loop {
+                if let Some(hash) = buffered.pop_front() {
+                    return Ok(hash);
+                }
+
+                let hashes: Vec<B256> = provider.get_filter_changes(*filter_id).await?;
+                if hashes.is_empty() {
+                    tokio::time::sleep(*poll_interval).await;
+                }
+                buffered.extend(hashes);
+            },
+        }
+    }
+}
+
+pub async fn establish_connection(url: &str) -> Result<Connection> {
+    let kind = TransportKind::from_url(url)?;
+
+    let provider = match kind {
+        TransportKind::Ws => {
+            let ws = WsConnect::new(url);
+            ProviderBuilder::new().on_ws(ws).await?.boxed()
+        }
+        TransportKind::Ipc => {
+            let path = url
+                .strip_prefix("ipc://")
+                .or_else(|| url.strip_prefix("file://"))
+                .unwrap_or(url);
+            let ipc = IpcConnect::new(PathBuf::from(path));
+            ProviderBuilder::new().on_ipc(ipc).await?.boxed()
+        }
+        TransportKind::Http => {
+            let http_url = url.parse().context("invalid http rpc url")?;
+            ProviderBuilder::new().on_http(http_url).boxed()
+        }
+    };
+
+    let poll_interval_ms = std::env::var("HTTP_POLL_INTERVAL_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_POLL_INTERVAL_MS);
+
+    Ok(Connection {
+        provider,
+        kind,
+        poll_interval: Duration::from_millis(poll_interval_ms),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn error_resp(code: i64, message: &str) -> TransportError {
+        let payload = json!({ "code": code, "message": message });
+        RpcError::ErrorResp(serde_json::from_value(payload).expect("valid error payload"))
+    }
+
+    #[test]
+    fn test_classify_transport_errors() {
+        assert!(matches!(
+            IngestError::from(TransportErrorKind::backend_gone()),
+            IngestError::ConnectionClosed
+        ));
+        assert!(matches!(
+            IngestError::from(TransportErrorKind::pubsub_unavailable()),
+            IngestError::SubscriptionNotFound
+        ));
+        assert!(matches!(
+            IngestError::from(TransportErrorKind::http_error(429, "slow down".into())),
+            IngestError::RateLimited
+        ));
+        assert!(matches!(
+            IngestError::from(TransportErrorKind::http_error(500, "oops".into())),
+            IngestError::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_classify_error_responses() {
+        assert!(matches!(
+            IngestError::from(error_resp(-32000, "subscription not found")),
+            IngestError::SubscriptionNotFound
+        ));
+        assert!(matches!(
+            IngestError::from(error_resp(-32601, "the method eth_subscribe does not exist")),
+            IngestError::SubscriptionNotFound
+        ));
+        assert!(matches!(
+            IngestError::from(error_resp(-32000, "filter not found")),
+            IngestError::SubscriptionNotFound
+        ));
+        assert!(matches!(
+            IngestError::from(error_resp(-32005, "daily request count exceeded")),
+            IngestError::RateLimited
+        ));
+        assert!(matches!(
+            IngestError::from(error_resp(-32000, "Rate limit reached")),
+            IngestError::RateLimited
+        ));
+        assert!(matches!(
+            IngestError::from(error_resp(-32000, "execution reverted")),
+            IngestError::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_classify_recv_errors() {
+        assert!(matches!(
+            IngestError::from(RecvError::Lagged(42)),
+            IngestError::Lagged(42)
+        ));
+        assert!(matches!(
+            IngestError::from(RecvError::Closed),
+            IngestError::ConnectionClosed
+        ));
+    }
+
+    #[test]
+    fn test_transport_kind_from_url() {
+        assert_eq!(
+            TransportKind::from_url("wss://sepolia.infura.io/ws/v3/key").unwrap(),
+            TransportKind::Ws
+        );
+        assert_eq!(
+            TransportKind::from_url("ws://127.0.0.1:8546").unwrap(),
+            TransportKind::Ws
+        );
+        assert_eq!(
+            TransportKind::from_url("https://sepolia.infura.io/v3/key").unwrap(),
+            TransportKind::Http
+        );
+        assert_eq!(
+            TransportKind::from_url("ipc:///var/run/geth.ipc").unwrap(),
+            TransportKind::Ipc
+        );
+        assert_eq!(
+            TransportKind::from_url("/home/eth/.ethereum/geth.ipc").unwrap(),
+            TransportKind::Ipc
+        );
+        assert!(TransportKind::from_url("ftp://example.com").is_err());
+        assert!(TransportKind::from_url("localhost:8545").is_err());
+    }
+
+    #[test]
+    fn test_retry_policies() {
+        assert_eq!(IngestError::Lagged(10).retry_delay(3), Duration::ZERO);
+        assert_eq!(
+            IngestError::SubscriptionNotFound.retry_delay(5),
+            Duration::from_secs(1)
+        );
+
+        assert_eq!(IngestError::ConnectionClosed.retry_delay(0), Duration::from_secs(1));
+        assert_eq!(IngestError::ConnectionClosed.retry_delay(3), Duration::from_secs(8));
+        assert_eq!(IngestError::ConnectionClosed.retry_delay(40), MAX_RETRY_DELAY);
+
+        assert_eq!(IngestError::RateLimited.retry_delay(0), Duration::from_secs(5));
+        assert_eq!(IngestError::RateLimited.retry_delay(2), Duration::from_secs(20));
+        assert_eq!(IngestError::RateLimited.retry_delay(40), MAX_RATE_LIMIT_DELAY);
+    }
 }
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_WINDOW: usize = 10_000;
+pub const DEFAULT_REPORT_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Timings {
+    pub received_at: Instant,
+    pub fetched_at: Option<Instant>,
+    pub decoded_at: Option<Instant>,
+    pub dispatched_at: Option<Instant>,
+    pub completed_at: Option<Instant>,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl Timings {
+    pub fn start() -> Self {
+        Self {
+            received_at: Instant::now(),
+            fetched_at: None,
+            decoded_at: None,
+            dispatched_at: None,
+            completed_at: None,
+        }
+    }
+
+    pub fn stages(&self) -> Vec<(&'static str, Duration)> {
+        let points = [
+            ("fetch", Some(self.received_at), self.fetched_at),
+            ("decode", self.fetched_at, self.decoded_at),
+            ("dispatch", self.decoded_at, self.dispatched_at),
+            ("action", self.dispatched_at, self.completed_at),
+            ("end_to_end", Some(self.received_at), self.completed_at),
+        ];
+
+        points
+            .into_iter()
+            .filter_map(|(stage, from, to)| Some((stage, to?.saturating_duration_since(from?))))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percentiles {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+pub struct LatencyWindow {
+    capacity: usize,
+    samples: BTreeMap<&'static str, VecDeque<Duration>>,
+}
+
+impl Default for LatencyWindow {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl LatencyWindow {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            samples: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, stage: &'static str, latency: Duration) {
+        let samples = self.samples.entry(stage).or_default();
+        if samples.len() >= self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+
+    pub fn percentiles(&self, stage: &str) -> Option<Percentiles> {
+        let samples = self.samples.get(stage)?;
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+
+        let at = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+
+        Some(Percentiles {
+            p50: at(0.50),
+            p90: at(0.90),
+            p99: at(0.99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        for stage in self.samples.keys() {
+            if let Some(p) = self.percentiles(stage) {
+                let _ = write!(
+                    out,
+                    "{}{}: p50={:?} p90={:?} p99={:?} max={:?}",
+                    if out.is_empty() { "" } else { " | " },
+                    stage,
+                    p.p50,
+                    p.p90,
+                    p.p99,
+                    p.max
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stages_from_timings() {
+        let start = Instant::now();
+        let timings = Timings {
+            received_at: start,
+            fetched_at: Some(start + Duration::from_millis(40)),
+            decoded_at: Some(start + Duration::from_millis(45)),
+            dispatched_at: Some(start + Duration::from_millis(46)),
+            completed_at: Some(start + Duration::from_millis(146)),
+        };
+
+        let stages: BTreeMap<_, _> = timings.stages().into_iter().collect();
+        assert_eq!(stages["fetch"], Duration::from_millis(40));
+        assert_eq!(stages["decode"], Duration::from_millis(5));
+        assert_eq!(stages["dispatch"], Duration::from_millis(1));
+        assert_eq!(stages["action"], Duration::from_millis(100));
+        assert_eq!(stages["end_to_end"], Duration::from_millis(146));
+    }
+
+    #[test]
+    fn test_incomplete_timings_skip_stages() {
+        let mut timings = Timings::start();
+        timings.fetched_at = Some(timings.received_at + Duration::from_millis(10));
+
+        let stages = timings.stages();
+        assert_eq!(stages.len(), 1);
+        assert_eq!(stages[0].0, "fetch");
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut window = LatencyWindow::new(1000);
+        for ms in 1..=100 {
+            window.record("fetch", Duration::from_millis(ms));
+        }
+
+        let p = window.percentiles("fetch").unwrap();
+        assert_eq!(p.p50, Duration::from_millis(51));
+        assert_eq!(p.p90, Duration::from_millis(90));
+        assert_eq!(p.p99, Duration::from_millis(99));
+        assert_eq!(p.max, Duration::from_millis(100));
+        assert!(window.percentiles("action").is_none());
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        let mut window = LatencyWindow::new(3);
+        for ms in [500, 1, 2, 3] {
+            window.record("decode", Duration::from_millis(ms));
+        }
+
+        let p = window.percentiles("decode").unwrap();
+        assert_eq!(p.max, Duration::from_millis(3), "oldest sample should be evicted");
+    }
+}
@@ -1,4 +1,33 @@
 pub mod action;
+pub mod alert_queue;
+pub mod backfill;
+pub mod batch;
+pub mod bus;
+pub mod chain;
 pub mod decoder;
+pub mod dedup;
+pub mod digest;
 pub mod discord;
+pub mod feed;
+pub mod filter;
+pub mod format;
 pub mod ingestor;
+pub mod latency;
+pub mod metrics;
+pub mod overflow;
+pub mod pipeline;
+pub mod replay;
+pub mod retry;
+pub mod shutdown;
+pub mod slack;
+pub mod supervisor;
+pub mod telegram;
+pub mod template;
+pub mod throttle;
+pub mod webhook;
+#[cfg(test)]
+mod fixtures;
+#[cfg(test)]
+mod mock_http;
+#[cfg(test)]
+mod mock_node;
@@ -5,275 +5,218 @@ use tikv_jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
-mod action;
-mod decoder;
-mod discord;
-mod ingestor;
-
-use action::SniperAction;
-use alloy::consensus::Transaction;
-use alloy::primitives::U256;
-use alloy::providers::{Provider, RootProvider};
-use alloy::pubsub::PubSubFrontend;
-use alloy::rpc::types::Transaction as RpcTransaction;
-use tokio::sync::mpsc;
+use alloy::providers::Provider;
+use mempool_sniper::action::{self, SniperAction};
+use mempool_sniper::backfill::BackfillConfig;
+use mempool_sniper::metrics::{self, metrics};
+use mempool_sniper::pipeline::{Sniper, SniperConfig, Source};
+use mempool_sniper::replay::{self, ReplayConfig};
+use mempool_sniper::{
+    alert_queue, bus, chain, dedup, discord, feed, filter, format, ingestor, latency, shutdown, slack, telegram, webhook,
+};
+use std::sync::Arc;
 use tracing::info;
 
-const CHANNEL_CAPACITY: usize = 500_000;
-const MAX_CONCURRENT_FETCHES: usize = 50;
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
 
-async fn spawn_ingestor(
-    tx_channel: mpsc::Sender<RpcTransaction>,
-    provider: RootProvider<PubSubFrontend>,
-) {
-    info!("ingestor started");
+    dotenvy::dotenv().ok();
 
-    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(MAX_CONCURRENT_FETCHES));
+    let started_at = std::time::Instant::now();
 
-    let mut retry_delay_secs = 1;
-    const MAX_RETRY_DELAY: u64 = 60;
+    info!("mempool sniper initialized");
 
-    loop {
-        let mut sub = match provider.subscribe_pending_transactions().await {
-            Ok(sub) => {
-                info!("subscribed to pending transactions (hash stream)");
-                retry_delay_secs = 1;
-                sub
-            }
-            Err(e) => {
-                let error_msg = format!("{:?}", e);
-                if error_msg.contains("subscription not found") {
-                    tracing::error!("subscription error: subscription not found (provider may not support eth_subscribe)");
-                } else if error_msg.contains("connection closed") || error_msg.contains("ConnectionClosed") {
-                    tracing::error!("subscription error: connection closed (network issue or provider restart)");
-                } else if error_msg.contains("rate limit") || error_msg.contains("429") {
-                    tracing::error!("subscription error: rate limit exceeded (too many requests)");
-                } else if error_msg.contains("timeout") || error_msg.contains("Timeout") {
-                    tracing::error!("subscription error: connection timeout");
-                } else {
-                    tracing::error!("subscription error: {} - {}", error_msg, e);
-                }
+    let use_discord = std::env::var("USE_DISCORD").unwrap_or_else(|_| "false".to_string());
 
-                tracing::warn!("retrying subscription in {} seconds (exponential backoff)...", retry_delay_secs);
-                tokio::time::sleep(tokio::time::Duration::from_secs(retry_delay_secs)).await;
-                
-                retry_delay_secs = (retry_delay_secs * 2).min(MAX_RETRY_DELAY);
-                continue;
-            }
-        };
+    let (source, chain_id) = match ReplayConfig::from_env() {
+        Some(config) => {
+            info!("replay mode: {} at {}x speed", config.path.display(), config.speed);
+            let chain_id = config.chain_id().await?.unwrap_or_else(|| {
+                tracing::warn!("no chain id found in replay file - assuming mainnet");
+                1
+            });
+            (Source::Replay(config), chain_id)
+        }
+        None => {
+            let rpc_url = std::env::var("RPC_URL")
+                .or_else(|_| std::env::var("WSS_RPC_URL"))
+                .expect("RPC_URL (or WSS_RPC_URL) must be set in .env file");
 
-        info!("full capture mode: processing 100% of all transactions (zero filtering)");
+            info!("connecting to rpc: {}", rpc_url);
 
-        let mut tx_count = 0u64;
-        
-        loop {
-            match sub.recv().await {
-                Ok(tx_hash) => {
-                    tx_count += 1;
-                    
-                    if tx_count % 10 == 0 {
-                        tracing::debug!("scanned {} transactions...", tx_count);
-                    }
-                    
-                    if tx_count % 100 == 0 {
-                        tracing::info!("processed {} transactions", tx_count);
-                    }
+            let conn = ingestor::establish_connection(&rpc_url).await?;
 
-                    let permit = semaphore.clone().acquire_owned().await.unwrap();
-
-                    let provider_clone = provider.clone();
-                    let tx_channel_clone = tx_channel.clone();
-
-                    tokio::spawn(async move {
-                        let _permit = permit;
-
-                        match provider_clone.get_transaction_by_hash(tx_hash).await {
-                            Ok(Some(tx)) => {
-                                let input_data = tx.inner.input();
-
-                                if !decoder::is_target_transaction(input_data) {
-                                    return;
-                                }
-
-                                tracing::debug!("target selector detected in mempool: {}", tx_hash);
-
-                                if let Err(e) = tx_channel_clone.try_send(tx) {
-                                    match e {
-                                        mpsc::error::TrySendError::Full(_) => {
-                                            tracing::warn!(
-                                                "buffer full - dropping target tx: {}",
-                                                tx_hash
-                                            );
-                                        }
-                                        mpsc::error::TrySendError::Closed(_) => {
-                                            tracing::error!("channel closed, stopping ingestor");
-                                        }
-                                    }
-                                }
-                            }
-                            Ok(None) => {
-                                tracing::debug!("tx: {} | not found", tx_hash);
-                            }
-                            Err(e) => {
-                                tracing::debug!("tx: {} | error fetching: {:?}", tx_hash, e);
-                            }
-                        }
-                    });
-                }
-                Err(e) => {
-                    let error_msg = format!("{:?}", e);
-                    
-                    if error_msg.contains("Lagged") {
-                        tracing::info!("subscription lagged by {} messages - reconnecting", 
-                            error_msg.split("Lagged(").nth(1)
-                                .and_then(|s| s.split(')').next())
-                                .unwrap_or("?"));
-                    } else if error_msg.contains("subscription not found") {
-                        tracing::error!("subscription not found - reconnecting");
-                    } else if error_msg.contains("connection closed") || error_msg.contains("ConnectionClosed") {
-                        tracing::error!("connection closed - reconnecting");
-                    } else if error_msg.contains("rate limit") || error_msg.contains("429") {
-                        tracing::error!("rate limit exceeded - reconnecting");
-                    } else {
-                        tracing::info!("subscription error: {} - reconnecting", e);
-                    }
+            info!("connected successfully ({})", conn.kind);
 
-                    break;
+            let chain_id = conn.provider.get_chain_id().await?;
+            match BackfillConfig::from_env()? {
+                Some(config) => {
+                    match config.to {
+                        Some(to) => info!("backfill mode: blocks {} to {}", config.from, to),
+                        None => info!("backfill mode: blocks {} to latest", config.from),
+                    }
+                    (Source::Backfill(conn, config), chain_id)
                 }
+                None => (Source::Rpc(conn), chain_id),
             }
         }
-    }
-}
-
-async fn spawn_consumer(
-    mut rx_channel: mpsc::Receiver<RpcTransaction>,
-    action: std::sync::Arc<dyn SniperAction>,
-) {
-    info!("consumer started");
-    info!("full capture mode: processing all transactions (no value threshold)");
-
-    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(50));
-    info!("parallel consumer engine: 50 concurrent workers");
-
-    loop {
-        let tx = match rx_channel.recv().await {
-            Some(tx) => tx,
-            None => {
-                info!("consumer stopped - channel closed");
-                break;
-            }
-        };
-
-        let permit = match semaphore.clone().acquire_owned().await {
-            Ok(p) => p,
-            Err(_) => {
-                tracing::error!("semaphore closed");
-                break;
-            }
-        };
+    };
 
-        let action_clone = action.clone();
+    let explorer = chain::Explorer::from_env(chain_id);
+    match chain::lookup(chain_id) {
+        Some(chain) => info!("chain: {} ({})", chain.name, chain_id),
+        None => tracing::warn!("unknown chain id {} - explorer links disabled unless EXPLORER_URL is set", chain_id),
+    }
 
-        tokio::spawn(async move {
-            let _permit = permit;
+    let mut sinks: Vec<Arc<dyn SniperAction>> = Vec::new();
 
-            let tx_hash = tx.inner.tx_hash();
-            let input_data = tx.inner.input();
-            let tx_value = tx.inner.value();
+    if use_discord.to_lowercase() == "true" {
+        let webhook_url = std::env::var("DISCORD_WEBHOOK_URL")
+            .expect("DISCORD_WEBHOOK_URL must be set when USE_DISCORD=true");
 
-            tracing::info!("processing tx: {:?}", tx_hash);
+        info!("discord webhook mode enabled");
 
-            let decoded = match decoder::decode_transaction(input_data, tx_value) {
-                Ok(decoded) => decoded,
-                Err(e) => {
-                    tracing::debug!("failed to decode tx {}: {}", tx_hash, e);
-                    return;
-                }
+        sinks.push(Arc::new(discord::DiscordClient::new(
+            &webhook_url,
+            format::Formatter::load("discord", discord::DEFAULT_TEMPLATE, &explorer)?,
+        )?));
+
+        if let Some(digest_url) = std::env::var("DISCORD_DIGEST_WEBHOOK_URL").ok().filter(|u| !u.is_empty()) {
+            info!("discord digest channel enabled");
+            let config = discord::DiscordConfig {
+                mode: discord::AlertMode::Digest,
+                ..discord::DiscordConfig::from_env(format::Formatter::load(
+                    "discord",
+                    discord::DEFAULT_TEMPLATE,
+                    &explorer,
+                )?)
             };
+            sinks.push(Arc::new(discord::DiscordClient::with_config(
+                &digest_url,
+                config,
+                alert_queue::AlertQueue::new(
+                    alert_queue::DEFAULT_CAPACITY,
+                    alert_queue::DropPolicy::DropLowestValue,
+                    None,
+                )
+                .with_depth(metrics().alert_queue_depth.with_label("discord_digest")),
+            )?));
+        }
+    }
 
-            let detected_at = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64;
-            
-            let target_tx = action::TargetTransaction {
-                tx_hash: *tx_hash,
-                from: tx.from,
-                value: decoded.effective_value,
-                method: decoded.method,
-                amount_out_min: decoded.amount_out_min,
-                path: decoded.path,
-                to: decoded.to,
-                deadline: decoded.deadline,
-                detected_at,
-            };
+    if let Some(config) = telegram::TelegramConfig::from_env(format::Formatter::load(
+        "telegram",
+        telegram::DEFAULT_TEMPLATE,
+        &explorer,
+    )?) {
+        info!("telegram sink enabled");
+        sinks.push(Arc::new(telegram::TelegramSink::new(config)));
+    }
 
-            if let Err(e) = action_clone.execute(&target_tx).await {
-                tracing::error!("failed to execute action for tx {}: {}", tx_hash, e);
-            }
-        });
+    if let Some(config) = slack::SlackConfig::from_env(format::Formatter::load("slack", slack::DEFAULT_TEMPLATE, &explorer)?) {
+        info!("slack sink enabled");
+        sinks.push(Arc::new(slack::SlackSink::new(config)));
     }
-}
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
-        .init();
+    if let Some(config) = webhook::WebhookConfig::from_env(Some(chain_id)) {
+        info!("webhook sink enabled");
+        sinks.push(Arc::new(webhook::WebhookSink::new(config)?));
+    }
 
-    dotenvy::dotenv().ok();
+    if let Some(publisher) = bus::BusPublisher::from_env(Some(chain_id))? {
+        info!("message bus publisher enabled");
+        sinks.push(Arc::new(publisher));
+    }
 
-    info!("mempool sniper initialized");
+    if let Some(config) = feed::FeedConfig::from_env(Some(chain_id)) {
+        let feed = feed::FeedServer::bind(config).await?;
+        info!("detection feed listening on ws://{0}/ws and http://{0}/events", feed.local_addr());
+        sinks.push(Arc::new(feed));
+    }
 
-    let wss_url = std::env::var("WSS_RPC_URL").expect("WSS_RPC_URL must be set in .env file");
-    let use_discord = std::env::var("USE_DISCORD").unwrap_or_else(|_| "false".to_string());
+    if sinks.is_empty() {
+        info!("console logger mode");
+        sinks.push(Arc::new(action::ConsoleLogger::new(format::Formatter::load(
+            "console",
+            action::DEFAULT_CONSOLE_TEMPLATE,
+            &explorer,
+        )?)));
+    }
 
-    info!("connecting to rpc: {}", std::env::var("WSS_RPC_URL").unwrap_or_else(|_| "unknown".to_string()));
-    info!("connecting to websocket: {}", wss_url);
+    let mut builder = Sniper::builder()
+        .source(source)
+        .config(SniperConfig::from_env())
+        .dedup(dedup::SeenSet::from_env());
 
-    let provider = ingestor::establish_connection(&wss_url).await?;
+    for sink in sinks {
+        builder = builder.sink(sink);
+    }
 
-    info!("connected successfully");
+    if let Some(source) = std::env::var("FILTER").ok().filter(|f| !f.trim().is_empty()) {
+        builder = builder.filter(filter::Filter::parse(&source).map_err(|e| anyhow::anyhow!("invalid FILTER: {}", e))?);
+    }
 
-    let action: std::sync::Arc<dyn SniperAction> = if use_discord.to_lowercase() == "true" {
-        let webhook_url = std::env::var("DISCORD_WEBHOOK_URL")
-            .expect("DISCORD_WEBHOOK_URL must be set when USE_DISCORD=true");
+    if let Some(recorder) = replay::Recorder::from_env()? {
+        builder = builder.recorder(recorder);
+    }
 
-        info!("discord webhook mode enabled");
+    let sniper = builder.build()?;
 
-        let discord_client = discord::DiscordClient::new(&webhook_url)?;
-        std::sync::Arc::new(discord_client)
-    } else {
-        info!("console logger mode");
-        std::sync::Arc::new(action::ConsoleLogger::new())
-    };
+    if let Ok(metrics_addr) = std::env::var("METRICS_ADDR") {
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(&metrics_addr).await {
+                tracing::error!("metrics endpoint stopped: {}", e);
+            }
+        });
+    }
 
-    let (tx, rx) = mpsc::channel::<RpcTransaction>(CHANNEL_CAPACITY);
+    let report_secs = std::env::var("LATENCY_REPORT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(latency::DEFAULT_REPORT_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(report_secs.max(1)));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let report = metrics().latency.lock().unwrap().report();
+            if !report.is_empty() {
+                info!("latency {}", report);
+            }
+        }
+    });
 
-    info!("spawning ingestor and consumer tasks...");
+    let handle = sniper.handle();
+    tokio::spawn(async move {
+        let signal = shutdown::signal().await;
+        info!("received {} - shutting down", signal);
+        handle.shutdown();
 
-    let ingestor_handle = tokio::spawn(spawn_ingestor(tx, provider));
+        let signal = shutdown::signal().await;
+        tracing::warn!("received {} again - exiting without draining", signal);
+        std::process::exit(130);
+    });
 
-    let consumer_handle = tokio::spawn(spawn_consumer(rx, action));
+    let result = sniper.run().await;
 
-    tokio::select! {
-        _ = ingestor_handle => {
-            tracing::error!("ingestor task terminated unexpectedly");
-        }
-        _ = consumer_handle => {
-            tracing::error!("consumer task terminated unexpectedly");
-        }
-    }
+    info!("shutdown complete - {}", metrics().summary(started_at.elapsed()));
 
-    Ok(())
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mempool_sniper::decoder;
+    use tokio::sync::mpsc;
+    use alloy::primitives::U256;
 
     #[tokio::test]
     async fn test_channel_overflow_behavior() {
@@ -346,7 +289,7 @@ mod tests {
         let eth_threshold = U256::from(100_000_000_000_000_000u128);
         assert!(tx_value >= eth_threshold, "Stage 3 value check should pass");
 
-        let logger = action::ConsoleLogger::new();
+        let logger = action::ConsoleLogger::default();
         let detected_at = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -362,6 +305,8 @@ mod tests {
             to: decoded.to,
             deadline: decoded.deadline,
             detected_at,
+            block_number: None,
+            timings: latency::Timings::default(),
         };
 
         let result = logger.execute(&target_tx).await;
@@ -0,0 +1,599 @@
+use crate::latency::{LatencyWindow, Timings};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const LATENCY_BUCKETS_SECS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+#[derive(Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Default)]
+pub struct Gauge(AtomicU64);
+
+impl Gauge {
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn dec(&self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Default)]
+pub struct LabeledGauge(Mutex<BTreeMap<String, Arc<Gauge>>>);
+
+impl LabeledGauge {
+    pub fn with_label(&self, label: &str) -> Arc<Gauge> {
+        self.0.lock().unwrap().entry(label.to_string()).or_default().clone()
+    }
+
+    pub fn get(&self, label: &str) -> u64 {
+        self.0.lock().unwrap().get(label).map(|gauge| gauge.get()).unwrap_or(0)
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str, label: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (value, gauge) in self.0.lock().unwrap().iter() {
+            let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, escape(value), gauge.get());
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS_SECS.len()],
+    count: u64,
+    sum_secs: f64,
+}
+
+impl Histogram {
+    pub fn observe(&mut self, value: Duration) {
+        let secs = value.as_secs_f64();
+        for (bucket, le) in self.buckets.iter_mut().zip(LATENCY_BUCKETS_SECS) {
+            if secs <= le {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_secs += secs;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        for (bucket, le) in self.buckets.iter().zip(LATENCY_BUCKETS_SECS) {
+            let _ = writeln!(out, "{}_bucket{{{}{}le=\"{}\"}} {}", name, labels, sep, le, bucket);
+        }
+        let _ = writeln!(out, "{}_bucket{{{}{}le=\"+Inf\"}} {}", name, labels, sep, self.count);
+        let braces = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+        let _ = writeln!(out, "{}_sum{} {}", name, braces, self.sum_secs);
+        let _ = writeln!(out, "{}_count{} {}", name, braces, self.count);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StageHealth {
+    pub up: bool,
+    pub restarts: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    pub hashes_received: Counter,
+    pub fetch_ok: Counter,
+    pub fetch_not_found: Counter,
+    pub fetch_error: Counter,
+    pub fetch_rate_limited: Counter,
+    pub fetch_timeout: Counter,
+    pub rpc_budget_waits: Counter,
+    pub fetch_batches: Counter,
+    pub fetch_batched_hashes: Counter,
+    pub fetch_retry_scheduled: Counter,
+    pub fetch_retry_recovered: Counter,
+    pub fetch_retry_exhausted: Counter,
+    pub fetch_retry_overflow: Counter,
+    pub recorded: Counter,
+    pub record_dropped: Counter,
+    pub filtered: Counter,
+    pub backfill_blocks: Counter,
+    pub backfill_missing: Counter,
+    pub backfill_transactions: Counter,
+    pub backfill_head: Gauge,
+    pub channel_depth: LabeledGauge,
+    pub channel_capacity: LabeledGauge,
+    pub dropped_full: Counter,
+    pub fetch_permits_in_use: Gauge,
+    pub fetch_permits_max: Gauge,
+    pub semaphore_saturated: Counter,
+    pub discord_send_failures: Counter,
+    pub telegram_send_failures: Counter,
+    pub slack_send_failures: Counter,
+    pub webhook_send_failures: Counter,
+    pub bus_publish_failures: Counter,
+    pub feed_clients: Gauge,
+    pub feed_dropped: Counter,
+    pub feed_slow_disconnects: Counter,
+    pub alert_queue_depth: LabeledGauge,
+    pub alerts_queued: Counter,
+    pub alerts_delivered: Counter,
+    pub alerts_retried: Counter,
+    pub alerts_dropped: Counter,
+    pub alerts_spilled: Counter,
+    pub reconnects: Counter,
+    decode_outcomes: Mutex<BTreeMap<String, u64>>,
+    action_latency: Mutex<BTreeMap<&'static str, Histogram>>,
+    stage_latency: Mutex<BTreeMap<&'static str, Histogram>>,
+    stages: Mutex<BTreeMap<(String, &'static str), StageHealth>>,
+    task_panics: Mutex<BTreeMap<&'static str, u64>>,
+    overflow: Mutex<BTreeMap<&'static str, u64>>,
+    pub latency: Mutex<LatencyWindow>,
+}
+
+impl Metrics {
+    pub fn record_decode(&self, method: &str) {
+        let mut outcomes = self.decode_outcomes.lock().unwrap();
+        *outcomes.entry(method.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn observe_action(&self, sink: &'static str, latency: Duration) {
+        let mut histograms = self.action_latency.lock().unwrap();
+        histograms.entry(sink).or_default().observe(latency);
+    }
+
+    pub fn observe_timings(&self, timings: &Timings) {
+        let stages = timings.stages();
+
+        let mut histograms = self.stage_latency.lock().unwrap();
+        for (stage, latency) in &stages {
+            histograms.entry(stage).or_default().observe(*latency);
+        }
+        drop(histograms);
+
+        let mut window = self.latency.lock().unwrap();
+        for (stage, latency) in stages {
+            window.record(stage, latency);
+        }
+    }
+
+    pub fn set_stage_up(&self, pipeline: &str, stage: &'static str, up: bool) {
+        self.stages.lock().unwrap().entry((pipeline.to_string(), stage)).or_default().up = up;
+    }
+
+    pub fn record_restart(&self, pipeline: &str, stage: &'static str, error: &str) {
+        let mut stages = self.stages.lock().unwrap();
+        let health = stages.entry((pipeline.to_string(), stage)).or_default();
+        health.restarts += 1;
+        health.last_error = Some(error.to_string());
+    }
+
+    pub fn stage_health(&self, pipeline: &str, stage: &'static str) -> Option<StageHealth> {
+        self.stages.lock().unwrap().get(&(pipeline.to_string(), stage)).cloned()
+    }
+
+    pub fn record_panic(&self, stage: &'static str) {
+        *self.task_panics.lock().unwrap().entry(stage).or_insert(0) += 1;
+    }
+
+    pub fn record_overflow(&self, policy: &'static str) {
+        *self.overflow.lock().unwrap().entry(policy).or_insert(0) += 1;
+    }
+
+    pub fn task_panics(&self, stage: &str) -> u64 {
+        self.task_panics.lock().unwrap().get(stage).copied().unwrap_or(0)
+    }
+
+    pub fn health(&self) -> (bool, serde_json::Value) {
+        let stages = self.stages.lock().unwrap();
+        let healthy = stages.values().all(|health| health.up);
+
+        let mut pipelines = serde_json::Map::new();
+        for ((pipeline, stage), health) in stages.iter() {
+            let entry = pipelines
+                .entry(pipeline.clone())
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+            entry[*stage] = serde_json::json!({
+                "up": health.up,
+                "restarts": health.restarts,
+                "last_error": health.last_error,
+                "task_panics": self.task_panics(stage),
+            });
+        }
+
+        (
+            healthy,
+            serde_json::json!({ "status": if healthy { "ok" } else { "degraded" }, "pipelines": pipelines }),
+        )
+    }
+
+    pub fn summary(&self, uptime: Duration) -> String {
+        let decoded: u64 = self
+            .decode_outcomes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(method, _)| method.as_str() != "decode_error")
+            .map(|(_, count)| count)
+            .sum();
+
+        format!(
+            "uptime {}s | {} hashes | {} fetched ({} not found, {} errors) | {} decoded | {} alerts delivered, {} dropped, {} spilled | {} dropped on full channel | {} not found recovered on retry, {} given up",
+            uptime.as_secs(),
+            self.hashes_received.get(),
+            self.fetch_ok.get(),
+            self.fetch_not_found.get(),
+            self.fetch_error.get(),
+            decoded,
+            self.alerts_delivered.get(),
+            self.alerts_dropped.get(),
+            self.alerts_spilled.get(),
+            self.dropped_full.get(),
+            self.fetch_retry_recovered.get(),
+            self.fetch_retry_exhausted.get() + self.fetch_retry_overflow.get(),
+        )
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        counter(&mut out, "sniper_hashes_received_total", "pending tx hashes received", self.hashes_received.get());
+
+        let _ = writeln!(out, "# HELP sniper_fetch_total transaction fetches by outcome");
+        let _ = writeln!(out, "# TYPE sniper_fetch_total counter");
+        let _ = writeln!(out, "sniper_fetch_total{{outcome=\"ok\"}} {}", self.fetch_ok.get());
+        let _ = writeln!(out, "sniper_fetch_total{{outcome=\"not_found\"}} {}", self.fetch_not_found.get());
+        let _ = writeln!(out, "sniper_fetch_total{{outcome=\"error\"}} {}", self.fetch_error.get());
+        let _ = writeln!(out, "sniper_fetch_total{{outcome=\"rate_limited\"}} {}", self.fetch_rate_limited.get());
+        let _ = writeln!(out, "sniper_fetch_total{{outcome=\"timeout\"}} {}", self.fetch_timeout.get());
+
+        let _ = writeln!(out, "# HELP sniper_fetch_retry_total not found fetch retries by outcome");
+        let _ = writeln!(out, "# TYPE sniper_fetch_retry_total counter");
+        let _ = writeln!(out, "sniper_fetch_retry_total{{outcome=\"scheduled\"}} {}", self.fetch_retry_scheduled.get());
+        let _ = writeln!(out, "sniper_fetch_retry_total{{outcome=\"recovered\"}} {}", self.fetch_retry_recovered.get());
+        let _ = writeln!(out, "sniper_fetch_retry_total{{outcome=\"exhausted\"}} {}", self.fetch_retry_exhausted.get());
+        let _ = writeln!(out, "sniper_fetch_retry_total{{outcome=\"overflow\"}} {}", self.fetch_retry_overflow.get());
+
+        let _ = writeln!(out, "# HELP sniper_decode_total decoded transactions by method");
+        let _ = writeln!(out, "# TYPE sniper_decode_total counter");
+        for (method, count) in self.decode_outcomes.lock().unwrap().iter() {
+            let _ = writeln!(out, "sniper_decode_total{{method=\"{}\"}} {}", escape(method), count);
+        }
+        counter(&mut out, "sniper_filtered_total", "decoded transactions rejected by the pipeline filter", self.filtered.get());
+
+        self.channel_depth
+            .render(&mut out, "sniper_channel_depth", "transactions waiting in the consumer channel", "pipeline");
+        self.channel_capacity
+            .render(&mut out, "sniper_channel_capacity", "consumer channel capacity", "pipeline");
+        counter(&mut out, "sniper_dropped_full_total", "target transactions dropped on a full channel", self.dropped_full.get());
+        let _ = writeln!(out, "# HELP sniper_ingest_overflow_total transactions lost to a full ingest queue by overflow policy");
+        let _ = writeln!(out, "# TYPE sniper_ingest_overflow_total counter");
+        for (policy, count) in self.overflow.lock().unwrap().iter() {
+            let _ = writeln!(out, "sniper_ingest_overflow_total{{policy=\"{}\"}} {}", policy, count);
+        }
+        gauge(&mut out, "sniper_fetch_permits_in_use", "fetch semaphore permits in use", self.fetch_permits_in_use.get());
+        gauge(&mut out, "sniper_fetch_permits_max", "current adaptive fetch concurrency limit", self.fetch_permits_max.get());
+        counter(&mut out, "sniper_fetch_batches_total", "json-rpc fetch batches sent", self.fetch_batches.get());
+        counter(&mut out, "sniper_fetch_batched_hashes_total", "hashes fetched through batches", self.fetch_batched_hashes.get());
+        counter(&mut out, "sniper_rpc_budget_waits_total", "fetches delayed by the compute unit budget", self.rpc_budget_waits.get());
+        counter(&mut out, "sniper_recorded_total", "fetched transactions written to the record file", self.recorded.get());
+        counter(&mut out, "sniper_record_dropped_total", "fetched transactions not recorded because the writer fell behind", self.record_dropped.get());
+        counter(&mut out, "sniper_backfill_blocks_total", "historical blocks backfilled", self.backfill_blocks.get());
+        counter(&mut out, "sniper_backfill_missing_blocks_total", "historical blocks the node did not return", self.backfill_missing.get());
+        counter(&mut out, "sniper_backfill_transactions_total", "mined transactions pushed through the pipeline by backfill", self.backfill_transactions.get());
+        gauge(&mut out, "sniper_backfill_head", "last block backfilled", self.backfill_head.get());
+        counter(&mut out, "sniper_semaphore_saturated_total", "hashes that waited on a saturated fetch semaphore", self.semaphore_saturated.get());
+
+        let _ = writeln!(out, "# HELP sniper_action_latency_seconds action execution latency per sink");
+        let _ = writeln!(out, "# TYPE sniper_action_latency_seconds histogram");
+        for (sink, histogram) in self.action_latency.lock().unwrap().iter() {
+            histogram.render(&mut out, "sniper_action_latency_seconds", &format!("sink=\"{}\"", sink));
+        }
+
+        let _ = writeln!(out, "# HELP sniper_stage_latency_seconds per-stage pipeline latency");
+        let _ = writeln!(out, "# TYPE sniper_stage_latency_seconds histogram");
+        for (stage, histogram) in self.stage_latency.lock().unwrap().iter() {
+            histogram.render(&mut out, "sniper_stage_latency_seconds", &format!("stage=\"{}\"", stage));
+        }
+
+        let _ = writeln!(out, "# HELP sniper_stage_latency_quantile_seconds per-stage latency over the recent window");
+        let _ = writeln!(out, "# TYPE sniper_stage_latency_quantile_seconds gauge");
+        let window = self.latency.lock().unwrap();
+        for stage in ["fetch", "decode", "dispatch", "action", "end_to_end"] {
+            if let Some(p) = window.percentiles(stage) {
+                for (q, value) in [("0.5", p.p50), ("0.9", p.p90), ("0.99", p.p99)] {
+                    let _ = writeln!(
+                        out,
+                        "sniper_stage_latency_quantile_seconds{{stage=\"{}\",quantile=\"{}\"}} {}",
+                        stage,
+                        q,
+                        value.as_secs_f64()
+                    );
+                }
+            }
+        }
+        drop(window);
+
+        counter(&mut out, "sniper_discord_send_failures_total", "discord webhook deliveries that failed", self.discord_send_failures.get());
+        counter(&mut out, "sniper_telegram_send_failures_total", "telegram messages that failed", self.telegram_send_failures.get());
+        counter(&mut out, "sniper_slack_send_failures_total", "slack webhook deliveries that failed", self.slack_send_failures.get());
+        counter(&mut out, "sniper_webhook_send_failures_total", "signed webhook deliveries that failed", self.webhook_send_failures.get());
+        counter(&mut out, "sniper_bus_publish_failures_total", "message bus publishes that failed", self.bus_publish_failures.get());
+        gauge(&mut out, "sniper_feed_clients", "connected websocket/sse feed clients", self.feed_clients.get());
+        counter(&mut out, "sniper_feed_dropped_total", "detections skipped for lagging feed clients", self.feed_dropped.get());
+        counter(&mut out, "sniper_feed_slow_disconnects_total", "feed clients disconnected for not reading", self.feed_slow_disconnects.get());
+        self.alert_queue_depth
+            .render(&mut out, "sniper_alert_queue_depth", "alerts waiting in the outbound queue", "queue");
+        counter(&mut out, "sniper_alerts_queued_total", "alerts accepted into the outbound queue", self.alerts_queued.get());
+        counter(&mut out, "sniper_alerts_delivered_total", "alerts delivered by the outbound sender", self.alerts_delivered.get());
+        counter(&mut out, "sniper_alerts_retried_total", "alert delivery retries", self.alerts_retried.get());
+        counter(&mut out, "sniper_alerts_dropped_total", "alerts dropped by the queue drop policy", self.alerts_dropped.get());
+        counter(&mut out, "sniper_alerts_spilled_total", "alerts spilled to disk on a full queue", self.alerts_spilled.get());
+        counter(&mut out, "sniper_reconnects_total", "ingestor resubscriptions", self.reconnects.get());
+
+        let stages = self.stages.lock().unwrap();
+        let _ = writeln!(out, "# HELP sniper_stage_up whether a supervised pipeline stage is running");
+        let _ = writeln!(out, "# TYPE sniper_stage_up gauge");
+        for ((pipeline, stage), health) in stages.iter() {
+            let _ = writeln!(
+                out,
+                "sniper_stage_up{{pipeline=\"{}\",stage=\"{}\"}} {}",
+                escape(pipeline),
+                stage,
+                health.up as u64
+            );
+        }
+        let _ = writeln!(out, "# HELP sniper_stage_restarts_total supervised stage restarts after a crash or exit");
+        let _ = writeln!(out, "# TYPE sniper_stage_restarts_total counter");
+        for ((pipeline, stage), health) in stages.iter() {
+            let _ = writeln!(
+                out,
+                "sniper_stage_restarts_total{{pipeline=\"{}\",stage=\"{}\"}} {}",
+                escape(pipeline),
+                stage,
+                health.restarts
+            );
+        }
+        drop(stages);
+
+        let _ = writeln!(out, "# HELP sniper_task_panics_total panics caught in per-transaction tasks");
+        let _ = writeln!(out, "# TYPE sniper_task_panics_total counter");
+        for (stage, count) in self.task_panics.lock().unwrap().iter() {
+            let _ = writeln!(out, "sniper_task_panics_total{{stage=\"{}\"}} {}", stage, count);
+        }
+
+        out
+    }
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub async fn serve(addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind metrics endpoint on {}", addr))?;
+
+    tracing::info!("metrics endpoint listening on http://{}/metrics", listener.local_addr()?);
+
+    serve_listener(listener).await
+}
+
+async fn serve_listener(listener: TcpListener) -> Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let n = match stream.read(&mut buf).await {
+                Ok(n) => n,
+                Err(e) => {
+                    tracing::debug!("metrics request read failed: {}", e);
+                    return;
+                }
+            };
+
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let path = request.split_whitespace().nth(1).unwrap_or("");
+
+            let response = if path == "/metrics" {
+                let body = metrics().render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else if path == "/health" {
+                let (healthy, body) = metrics().health();
+                let body = body.to_string();
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    if healthy { "200 OK" } else { "503 Service Unavailable" },
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                tracing::debug!("metrics response write failed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = Histogram::default();
+        histogram.observe(Duration::from_millis(3));
+        histogram.observe(Duration::from_millis(200));
+        histogram.observe(Duration::from_secs(30));
+
+        assert_eq!(histogram.count, 3);
+        assert_eq!(histogram.buckets[0], 0, "1ms bucket");
+        assert_eq!(histogram.buckets[1], 1, "5ms bucket");
+        assert_eq!(histogram.buckets[6], 2, "250ms bucket");
+        assert_eq!(histogram.buckets[11], 2, "10s bucket");
+    }
+
+    #[test]
+    fn test_render_exposition_format() {
+        let m = Metrics::default();
+        m.hashes_received.inc();
+        m.hashes_received.inc();
+        m.fetch_not_found.inc();
+        m.channel_capacity.with_label("default").set(500_000);
+        m.channel_capacity.with_label("backfill").set(1_000);
+        m.record_decode("swapExactETHForTokens");
+        m.record_decode("Native Transfer");
+        m.observe_action("console", Duration::from_millis(2));
+
+        let out = m.render();
+
+        assert!(out.contains("sniper_hashes_received_total 2\n"));
+        assert!(out.contains("sniper_fetch_total{outcome=\"not_found\"} 1\n"));
+        assert!(out.contains("sniper_channel_capacity{pipeline=\"default\"} 500000\n"));
+        assert!(out.contains("sniper_channel_capacity{pipeline=\"backfill\"} 1000\n"));
+        assert!(out.contains("sniper_decode_total{method=\"swapExactETHForTokens\"} 1\n"));
+        assert!(out.contains("sniper_decode_total{method=\"Native Transfer\"} 1\n"));
+        assert!(out.contains("sniper_action_latency_seconds_bucket{sink=\"console\",le=\"0.005\"} 1\n"));
+        assert!(out.contains("sniper_action_latency_seconds_count{sink=\"console\"} 1\n"));
+    }
+
+    #[test]
+    fn test_shutdown_summary() {
+        let m = Metrics::default();
+        m.hashes_received.add(10);
+        m.fetch_ok.add(7);
+        m.fetch_not_found.add(3);
+        m.record_decode("multicall");
+        m.record_decode("multicall");
+        m.record_decode("decode_error");
+        m.alerts_delivered.inc();
+        m.fetch_retry_recovered.add(2);
+        m.fetch_retry_exhausted.inc();
+
+        let summary = m.summary(Duration::from_secs(90));
+        assert!(summary.starts_with("uptime 90s | 10 hashes | 7 fetched (3 not found, 0 errors) | 2 decoded | 1 alerts delivered"));
+        assert!(summary.ends_with("| 2 not found recovered on retry, 1 given up"));
+    }
+
+    #[test]
+    fn test_stage_health() {
+        let m = Metrics::default();
+        m.set_stage_up("default", "ingestor", true);
+        m.set_stage_up("default", "consumer", true);
+        m.set_stage_up("backfill", "ingestor", true);
+        m.record_restart("default", "ingestor", "panicked: boom");
+        m.record_panic("action");
+        m.record_overflow("drop_oldest");
+
+        let (healthy, body) = m.health();
+        assert!(healthy);
+        assert_eq!(body["pipelines"]["default"]["ingestor"]["restarts"], 1);
+        assert_eq!(body["pipelines"]["default"]["ingestor"]["last_error"], "panicked: boom");
+        assert_eq!(body["pipelines"]["backfill"]["ingestor"]["restarts"], 0, "pipelines are tracked separately");
+
+        let out = m.render();
+        assert!(out.contains("sniper_stage_up{pipeline=\"default\",stage=\"consumer\"} 1\n"));
+        assert!(out.contains("sniper_stage_restarts_total{pipeline=\"default\",stage=\"ingestor\"} 1\n"));
+        assert!(out.contains("sniper_stage_restarts_total{pipeline=\"backfill\",stage=\"ingestor\"} 0\n"));
+        assert!(out.contains("sniper_task_panics_total{stage=\"action\"} 1\n"));
+        assert!(out.contains("sniper_ingest_overflow_total{policy=\"drop_oldest\"} 1\n"));
+
+        m.set_stage_up("default", "consumer", false);
+        let (healthy, body) = m.health();
+        assert!(!healthy);
+        assert_eq!(body["status"], "degraded");
+    }
+
+    #[test]
+    fn test_render_stage_latency() {
+        let m = Metrics::default();
+        let mut timings = Timings::start();
+        timings.fetched_at = Some(timings.received_at + Duration::from_millis(20));
+        m.observe_timings(&timings);
+
+        let out = m.render();
+        assert!(out.contains("sniper_stage_latency_seconds_count{stage=\"fetch\"} 1\n"));
+        assert!(out.contains("sniper_stage_latency_quantile_seconds{stage=\"fetch\",quantile=\"0.5\"} 0.02\n"));
+        assert!(!out.contains("stage=\"action\""));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_serves_text() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_listener(listener));
+
+        metrics().reconnects.inc();
+
+        let body = reqwest::get(format!("http://{}/metrics", addr))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(body.contains("# TYPE sniper_reconnects_total counter"));
+
+        let status = reqwest::get(format!("http://{}/other", addr))
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,258 @@
+use alloy::primitives::B256;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+const JSONRPC_LIMIT_EXCEEDED: i64 = -32005;
+
+#[derive(Debug, Clone)]
+enum Event {
+    Announce(Vec<B256>),
+    Disconnect,
+}
+
+#[derive(Default)]
+struct NodeState {
+    txs: HashMap<B256, Value>,
+    blocks: BTreeMap<u64, Value>,
+    hidden: HashMap<B256, u32>,
+    rate_limited: u32,
+    next_subscription: u64,
+    calls: HashMap<String, usize>,
+    connections: usize,
+}
+
+pub struct MockNode {
+    pub url: String,
+    state: Arc<Mutex<NodeState>>,
+    events: broadcast::Sender<Event>,
+}
+
+impl MockNode {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(NodeState::default()));
+        let (events, _) = broadcast::channel(1024);
+
+        tokio::spawn({
+            let state = state.clone();
+            let events = events.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    state.lock().unwrap().connections += 1;
+                    tokio::spawn(serve(stream, state.clone(), events.subscribe()));
+                }
+            }
+        });
+
+        Self {
+            url: format!("ws://{}", addr),
+            state,
+            events,
+        }
+    }
+
+    pub fn add_tx(&self, tx: Value) -> B256 {
+        let hash: B256 = serde_json::from_value(tx["hash"].clone()).expect("tx fixture has a hash");
+        self.state.lock().unwrap().txs.insert(hash, tx);
+        hash
+    }
+
+    pub fn add_block(&self, number: u64, txs: Vec<Value>) -> Vec<B256> {
+        let block_hash = B256::left_padding_from(&(number | 1 << 63).to_be_bytes());
+        let txs: Vec<Value> = txs
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut tx)| {
+                tx["blockHash"] = json!(block_hash);
+                tx["blockNumber"] = json!(format!("{:#x}", number));
+                tx["transactionIndex"] = json!(format!("{:#x}", index));
+                tx
+            })
+            .collect();
+        let hashes = txs
+            .iter()
+            .map(|tx| serde_json::from_value(tx["hash"].clone()).expect("tx fixture has a hash"))
+            .collect();
+
+        let block = json!({
+            "hash": block_hash,
+            "parentHash": B256::ZERO,
+            "sha3Uncles": B256::ZERO,
+            "miner": "0x0000000000000000000000000000000000000000",
+            "stateRoot": B256::ZERO,
+            "transactionsRoot": B256::ZERO,
+            "receiptsRoot": B256::ZERO,
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "difficulty": "0x0",
+            "number": format!("{:#x}", number),
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x0",
+            "timestamp": format!("{:#x}", 1_700_000_000 + number * 12),
+            "extraData": "0x",
+            "mixHash": B256::ZERO,
+            "nonce": "0x0000000000000000",
+            "uncles": [],
+            "transactions": txs,
+        });
+        self.state.lock().unwrap().blocks.insert(number, block);
+        hashes
+    }
+
+    pub fn hide(&self, hash: B256, times: u32) {
+        self.state.lock().unwrap().hidden.insert(hash, times);
+    }
+
+    pub fn rate_limit(&self, requests: u32) {
+        self.state.lock().unwrap().rate_limited = requests;
+    }
+
+    pub fn announce(&self, hashes: &[B256]) {
+        let _ = self.events.send(Event::Announce(hashes.to_vec()));
+    }
+
+    pub fn disconnect(&self) {
+        let _ = self.events.send(Event::Disconnect);
+    }
+
+    pub fn calls(&self, method: &str) -> usize {
+        self.state.lock().unwrap().calls.get(method).copied().unwrap_or(0)
+    }
+
+    pub fn connections(&self) -> usize {
+        self.state.lock().unwrap().connections
+    }
+}
+
+async fn serve(stream: TcpStream, state: Arc<Mutex<NodeState>>, mut events: broadcast::Receiver<Event>) {
+    let Ok(mut socket) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let mut subscriptions: Vec<String> = Vec::new();
+
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => continue,
+                    _ => return,
+                };
+                let Ok(request) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+
+                let reply = match request {
+                    Value::Array(requests) => Value::Array(
+                        requests.iter().map(|r| handle(r, &state, &mut subscriptions)).collect(),
+                    ),
+                    request => handle(&request, &state, &mut subscriptions),
+                };
+                if socket.send(Message::text(reply.to_string())).await.is_err() {
+                    return;
+                }
+            }
+            event = events.recv() => match event {
+                Ok(Event::Announce(hashes)) => {
+                    for hash in hashes {
+                        for subscription in &subscriptions {
+                            let notification = json!({
+                                "jsonrpc": "2.0",
+                                "method": "eth_subscription",
+                                "params": { "subscription": subscription, "result": hash },
+                            });
+                            if socket.send(Message::text(notification.to_string())).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+                Ok(Event::Disconnect) | Err(_) => return,
+            },
+        }
+    }
+}
+
+fn handle(request: &Value, state: &Mutex<NodeState>, subscriptions: &mut Vec<String>) -> Value {
+    let id = request["id"].clone();
+    let method = request["method"].as_str().unwrap_or_default();
+
+    let mut state = state.lock().unwrap();
+    *state.calls.entry(method.to_string()).or_insert(0) += 1;
+
+    let result = match method {
+        "eth_subscribe" => {
+            state.next_subscription += 1;
+            let subscription = format!("{:#x}", state.next_subscription);
+            subscriptions.push(subscription.clone());
+            json!(subscription)
+        }
+        "eth_unsubscribe" => {
+            let subscription = request["params"][0].as_str().unwrap_or_default();
+            subscriptions.retain(|s| s != subscription);
+            json!(true)
+        }
+        "eth_chainId" => json!("0x1"),
+        "eth_blockNumber" => json!(format!("{:#x}", state.blocks.keys().next_back().copied().unwrap_or(0))),
+        "eth_getBlockByNumber" => {
+            let number = match request["params"][0].as_str().unwrap_or_default() {
+                "latest" => state.blocks.keys().next_back().copied(),
+                tag => u64::from_str_radix(tag.trim_start_matches("0x"), 16).ok(),
+            };
+            let full = request["params"][1].as_bool().unwrap_or(false);
+
+            match number.and_then(|number| state.blocks.get(&number)) {
+                Some(block) if full => block.clone(),
+                Some(block) => {
+                    let mut block = block.clone();
+                    let hashes: Vec<Value> = block["transactions"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|tx| tx["hash"].clone())
+                        .collect();
+                    block["transactions"] = json!(hashes);
+                    block
+                }
+                None => Value::Null,
+            }
+        }
+        "eth_getTransactionByHash" => {
+            if state.rate_limited > 0 {
+                state.rate_limited -= 1;
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": JSONRPC_LIMIT_EXCEEDED, "message": "rate limit exceeded" },
+                });
+            }
+
+            let hash: Option<B256> = serde_json::from_value(request["params"][0].clone()).ok();
+            match hash {
+                Some(hash) => match state.hidden.get_mut(&hash) {
+                    Some(times) if *times > 0 => {
+                        *times -= 1;
+                        Value::Null
+                    }
+                    _ => state.txs.get(&hash).cloned().unwrap_or(Value::Null),
+                },
+                None => Value::Null,
+            }
+        }
+        _ => {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("the method {} does not exist", method) },
+            });
+        }
+    };
+
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
@@ -0,0 +1,438 @@
+use crate::metrics::{metrics, Gauge};
+use alloy::primitives::U256;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+pub const DEFAULT_CAPACITY: usize = 500_000;
+pub const DEFAULT_BLOCK_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    DropNewest,
+    DropOldest,
+    DropLowestValue,
+    Block(Duration),
+}
+
+impl OverflowPolicy {
+    pub fn parse(s: &str, block_timeout: Duration) -> Option<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "drop-newest" => Some(OverflowPolicy::DropNewest),
+            "drop-oldest" => Some(OverflowPolicy::DropOldest),
+            "drop-lowest-value" => Some(OverflowPolicy::DropLowestValue),
+            "block" => Some(OverflowPolicy::Block(block_timeout)),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverflowPolicy::DropNewest => "drop_newest",
+            OverflowPolicy::DropOldest => "drop_oldest",
+            OverflowPolicy::DropLowestValue => "drop_lowest_value",
+            OverflowPolicy::Block(_) => "block",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendOutcome {
+    Queued,
+    Evicted,
+    Dropped,
+    TimedOut,
+    Closed,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueueConfig {
+    pub capacity: usize,
+    pub policy: OverflowPolicy,
+    pub priority: bool,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            policy: OverflowPolicy::DropNewest,
+            priority: false,
+        }
+    }
+}
+
+impl QueueConfig {
+    pub fn ranks_by_value(&self) -> bool {
+        self.priority || self.policy == OverflowPolicy::DropLowestValue
+    }
+
+    pub fn from_env() -> Self {
+        let block_timeout = std::env::var("INGEST_BLOCK_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_BLOCK_TIMEOUT);
+
+        let policy = match std::env::var("INGEST_OVERFLOW_POLICY") {
+            Ok(v) => OverflowPolicy::parse(&v, block_timeout).unwrap_or_else(|| {
+                tracing::warn!("unknown INGEST_OVERFLOW_POLICY {:?} - using drop-newest", v);
+                OverflowPolicy::DropNewest
+            }),
+            Err(_) => OverflowPolicy::DropNewest,
+        };
+
+        Self {
+            capacity: std::env::var("INGEST_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_CAPACITY),
+            policy,
+            priority: std::env::var("INGEST_PRIORITY")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+        }
+    }
+}
+
+struct State<T> {
+    items: BTreeMap<u64, (T, U256)>,
+    by_value: BTreeSet<(U256, Reverse<u64>)>,
+    seq: u64,
+    senders: usize,
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn remove(&mut self, seq: u64) -> Option<T> {
+        let (item, value) = self.items.remove(&seq)?;
+        self.by_value.remove(&(value, Reverse(seq)));
+        Some(item)
+    }
+
+    fn oldest(&self) -> Option<u64> {
+        self.items.keys().next().copied()
+    }
+
+    fn lowest(&self) -> Option<(U256, u64)> {
+        self.by_value.first().map(|(value, Reverse(seq))| (*value, *seq))
+    }
+
+    fn highest(&self) -> Option<u64> {
+        self.by_value.last().map(|(_, Reverse(seq))| *seq)
+    }
+}
+
+struct Shared<T> {
+    config: QueueConfig,
+    state: Mutex<State<T>>,
+    available: Notify,
+    space: Notify,
+    depth: Arc<Gauge>,
+}
+
+pub fn channel<T>(config: QueueConfig) -> (Sender<T>, Receiver<T>) {
+    channel_with_depth(config, Arc::default())
+}
+
+pub fn channel_with_depth<T>(config: QueueConfig, depth: Arc<Gauge>) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        config: QueueConfig {
+            capacity: config.capacity.max(1),
+            ..config
+        },
+        state: Mutex::new(State {
+            items: BTreeMap::new(),
+            by_value: BTreeSet::new(),
+            seq: 0,
+            senders: 1,
+            closed: false,
+        }),
+        available: Notify::new(),
+        space: Notify::new(),
+        depth,
+    });
+
+    (Sender(shared.clone()), Receiver(shared))
+}
+
+pub struct Sender<T>(Arc<Shared<T>>);
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.0.state.lock().unwrap().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.0.available.notify_one();
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    pub fn ranks_by_value(&self) -> bool {
+        self.0.config.ranks_by_value()
+    }
+
+    pub async fn send(&self, item: T, value: U256) -> SendOutcome {
+        let shared = &self.0;
+        let policy = shared.config.policy;
+
+        let outcome = match policy {
+            OverflowPolicy::Block(timeout) => {
+                let mut item = Some((item, value));
+                let wait = async {
+                    loop {
+                        let space = shared.space.notified();
+                        if let Some(outcome) = self.try_push(&mut item, false) {
+                            return outcome;
+                        }
+                        space.await;
+                    }
+                };
+
+                match tokio::time::timeout(timeout, wait).await {
+                    Ok(outcome) => outcome,
+                    Err(_) => SendOutcome::TimedOut,
+                }
+            }
+            _ => self
+                .try_push(&mut Some((item, value)), true)
+                .expect("non-blocking push always completes"),
+        };
+
+        if matches!(outcome, SendOutcome::Evicted | SendOutcome::Dropped | SendOutcome::TimedOut) {
+            metrics().dropped_full.inc();
+            metrics().record_overflow(policy.name());
+        }
+
+        outcome
+    }
+
+    pub async fn send_wait(&self, item: T, value: U256) -> SendOutcome {
+        let mut item = Some((item, value));
+
+        loop {
+            let space = self.0.space.notified();
+            if let Some(outcome) = self.try_push(&mut item, false) {
+                return outcome;
+            }
+            space.await;
+        }
+    }
+
+    pub fn close(&self) {
+        self.0.state.lock().unwrap().closed = true;
+        self.0.available.notify_one();
+        self.0.space.notify_waiters();
+    }
+
+    fn try_push(&self, item: &mut Option<(T, U256)>, evict: bool) -> Option<SendOutcome> {
+        let shared = &self.0;
+        let mut state = shared.state.lock().unwrap();
+
+        if state.closed {
+            return Some(SendOutcome::Closed);
+        }
+
+        let mut outcome = SendOutcome::Queued;
+
+        if state.items.len() >= shared.config.capacity {
+            if !evict {
+                return None;
+            }
+
+            let (_, value) = item.as_ref().expect("item is pushed once");
+            let victim = match shared.config.policy {
+                OverflowPolicy::DropOldest => state.oldest(),
+                OverflowPolicy::DropLowestValue => state.lowest().filter(|(lowest, _)| lowest < value).map(|(_, seq)| seq),
+                _ => None,
+            };
+
+            match victim {
+                Some(seq) => {
+                    state.remove(seq);
+                    outcome = SendOutcome::Evicted;
+                }
+                None => return Some(SendOutcome::Dropped),
+            }
+        }
+
+        let (item, value) = item.take().expect("item is pushed once");
+        let seq = state.seq;
+        state.seq += 1;
+        state.items.insert(seq, (item, value));
+        state.by_value.insert((value, Reverse(seq)));
+        shared.depth.set(state.items.len() as u64);
+        drop(state);
+
+        shared.available.notify_one();
+        Some(outcome)
+    }
+}
+
+#[derive(Clone)]
+pub struct Receiver<T>(Arc<Shared<T>>);
+
+impl<T> Receiver<T> {
+    pub async fn recv(&self) -> Option<T> {
+        let shared = &self.0;
+
+        loop {
+            {
+                let mut state = shared.state.lock().unwrap();
+                let next = if shared.config.priority { state.highest() } else { state.oldest() };
+
+                if let Some(seq) = next {
+                    let item = state.remove(seq);
+                    shared.depth.set(state.items.len() as u64);
+                    drop(state);
+                    shared.space.notify_one();
+                    return item;
+                }
+
+                if state.senders == 0 || state.closed {
+                    return None;
+                }
+            }
+
+            shared.available.notified().await;
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.0.state.lock().unwrap().items.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(capacity: usize, policy: OverflowPolicy, priority: bool) -> QueueConfig {
+        QueueConfig { capacity, policy, priority }
+    }
+
+    async fn fill(sender: &Sender<u32>, values: &[u32]) -> Vec<SendOutcome> {
+        let mut outcomes = Vec::new();
+        for value in values {
+            outcomes.push(sender.send(*value, U256::from(*value)).await);
+        }
+        outcomes
+    }
+
+    async fn drain(receiver: &Receiver<u32>) -> Vec<u32> {
+        let mut items = Vec::new();
+        while receiver.depth() > 0 {
+            items.push(receiver.recv().await.unwrap());
+        }
+        items
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        let timeout = Duration::from_millis(50);
+        assert_eq!(OverflowPolicy::parse("drop-oldest", timeout), Some(OverflowPolicy::DropOldest));
+        assert_eq!(OverflowPolicy::parse("DROP_LOWEST_VALUE", timeout), Some(OverflowPolicy::DropLowestValue));
+        assert_eq!(OverflowPolicy::parse("block", timeout), Some(OverflowPolicy::Block(timeout)));
+        assert_eq!(OverflowPolicy::parse("spill", timeout), None);
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest() {
+        let (sender, receiver) = channel(config(2, OverflowPolicy::DropNewest, false));
+        let outcomes = fill(&sender, &[5, 1, 9]).await;
+        assert_eq!(outcomes, vec![SendOutcome::Queued, SendOutcome::Queued, SendOutcome::Dropped]);
+        assert_eq!(drain(&receiver).await, vec![5, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest() {
+        let (sender, receiver) = channel(config(2, OverflowPolicy::DropOldest, false));
+        let outcomes = fill(&sender, &[5, 1, 9]).await;
+        assert_eq!(outcomes[2], SendOutcome::Evicted);
+        assert_eq!(drain(&receiver).await, vec![1, 9]);
+    }
+
+    #[tokio::test]
+    async fn test_drop_lowest_value() {
+        let (sender, receiver) = channel(config(2, OverflowPolicy::DropLowestValue, false));
+        let outcomes = fill(&sender, &[5, 1, 9, 0]).await;
+        assert_eq!(outcomes[2], SendOutcome::Evicted);
+        assert_eq!(outcomes[3], SendOutcome::Dropped, "lower than everything queued");
+        assert_eq!(drain(&receiver).await, vec![5, 9]);
+    }
+
+    #[tokio::test]
+    async fn test_priority_order() {
+        let (sender, receiver) = channel(config(10, OverflowPolicy::DropNewest, true));
+        fill(&sender, &[5, 1, 9, 5]).await;
+        assert_eq!(drain(&receiver).await, vec![9, 5, 5, 1]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_block_with_timeout() {
+        let (sender, receiver) = channel(config(1, OverflowPolicy::Block(Duration::from_millis(100)), false));
+        assert_eq!(sender.send(1, U256::ZERO).await, SendOutcome::Queued);
+        assert_eq!(sender.send(2, U256::ZERO).await, SendOutcome::TimedOut);
+
+        let blocked = tokio::spawn({
+            let sender = sender.clone();
+            async move { sender.send(3, U256::ZERO).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(receiver.recv().await, Some(1));
+
+        assert_eq!(blocked.await.unwrap(), SendOutcome::Queued);
+        assert_eq!(receiver.recv().await, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_closes_when_senders_dropped() {
+        let (sender, receiver) = channel(config(4, OverflowPolicy::DropNewest, false));
+        let second = sender.clone();
+        sender.send(1, U256::ZERO).await;
+        drop(sender);
+
+        second.send(2, U256::ZERO).await;
+        drop(second);
+
+        assert_eq!(receiver.recv().await, Some(1));
+        assert_eq!(receiver.recv().await, Some(2));
+        assert_eq!(receiver.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_send_wait_applies_backpressure_until_closed() {
+        let (sender, receiver) = channel(config(1, OverflowPolicy::DropNewest, false));
+        let producer = tokio::spawn({
+            let sender = sender.clone();
+            async move {
+                let mut outcomes = Vec::new();
+                for value in 0..5u32 {
+                    outcomes.push(sender.send_wait(value, U256::ZERO).await);
+                }
+                sender.close();
+                outcomes
+            }
+        });
+
+        let mut received = Vec::new();
+        while let Some(value) = receiver.recv().await {
+            received.push(value);
+        }
+
+        assert_eq!(received, vec![0, 1, 2, 3, 4]);
+        assert_eq!(producer.await.unwrap(), vec![SendOutcome::Queued; 5]);
+        assert_eq!(sender.send(9, U256::ZERO).await, SendOutcome::Closed);
+        assert_eq!(receiver.recv().await, None);
+    }
+}
@@ -0,0 +1,1438 @@
+use crate::action::{FanOut, SniperAction, TargetTransaction};
+use crate::backfill::{self, BackfillConfig};
+use crate::batch::{self, BatchConfig, Batcher};
+use crate::decoder::{Decoder, SelectorDecoder};
+use crate::dedup::{self, SeenSet};
+use crate::filter::Filter;
+use crate::ingestor::{self, Connection, IngestError, PendingTx, SniperProvider, TransportKind};
+use crate::latency::Timings;
+use crate::metrics::metrics;
+use crate::overflow::{self, QueueConfig};
+use crate::replay::{self, Recorder, ReplayConfig};
+use crate::retry::{self, PendingFetch, RetryConfig, RetryDecision, RetryQueue};
+use crate::shutdown::{self, DrainStage, Shutdown};
+use crate::supervisor::{self, StageExit};
+use crate::throttle::{self, AdaptiveLimiter, AimdConfig, ComputeBudget, FetchOutcome, FetchPermit};
+use alloy::consensus::Transaction;
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::info;
+
+pub const DEFAULT_MAX_CONCURRENT_ACTIONS: usize = 50;
+pub const DEFAULT_PIPELINE_NAME: &str = "default";
+
+#[derive(Clone)]
+pub enum Source {
+    Rpc(Connection),
+    Replay(ReplayConfig),
+    Backfill(Connection, BackfillConfig),
+}
+
+#[derive(Clone)]
+pub struct SniperConfig {
+    pub name: String,
+    pub queue: QueueConfig,
+    pub concurrency: AimdConfig,
+    pub batch: BatchConfig,
+    pub retry: RetryConfig,
+    pub budget: Option<Arc<ComputeBudget>>,
+    pub cu_per_fetch: u32,
+    pub drain_timeout: Duration,
+    pub max_concurrent_actions: usize,
+    pub full_capture: bool,
+}
+
+impl Default for SniperConfig {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PIPELINE_NAME.to_string(),
+            queue: QueueConfig::default(),
+            concurrency: AimdConfig::default(),
+            batch: BatchConfig::default(),
+            retry: RetryConfig::default(),
+            budget: None,
+            cu_per_fetch: throttle::DEFAULT_CU_PER_FETCH,
+            drain_timeout: Duration::from_secs(shutdown::DEFAULT_DRAIN_SECS),
+            max_concurrent_actions: DEFAULT_MAX_CONCURRENT_ACTIONS,
+            full_capture: true,
+        }
+    }
+}
+
+impl SniperConfig {
+    pub fn from_env() -> Self {
+        Self {
+            name: DEFAULT_PIPELINE_NAME.to_string(),
+            queue: QueueConfig::from_env(),
+            concurrency: AimdConfig::from_env(),
+            batch: BatchConfig::from_env(),
+            retry: RetryConfig::from_env(),
+            budget: ComputeBudget::from_env().map(Arc::new),
+            cu_per_fetch: std::env::var("RPC_CU_PER_FETCH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(throttle::DEFAULT_CU_PER_FETCH),
+            drain_timeout: shutdown::drain_timeout_from_env(),
+            max_concurrent_actions: std::env::var("MAX_CONCURRENT_ACTIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_ACTIONS),
+            full_capture: std::env::var("FULL_CAPTURE")
+                .map(|v| !v.eq_ignore_ascii_case("false"))
+                .unwrap_or(true),
+        }
+    }
+}
+
+struct Shared {
+    action: Arc<dyn SniperAction>,
+    decoder: Arc<dyn Decoder>,
+    filters: Vec<Filter>,
+    config: SniperConfig,
+    recorder: Option<Arc<Recorder>>,
+    seen: Mutex<SeenSet>,
+    shutdown: Shutdown,
+}
+
+impl Shared {
+    fn selects(&self, input_data: &[u8]) -> bool {
+        self.config.full_capture || self.decoder.selects(input_data)
+    }
+}
+
+#[derive(Default)]
+pub struct SniperBuilder {
+    source: Option<Source>,
+    sinks: Vec<Arc<dyn SniperAction>>,
+    decoder: Option<Arc<dyn Decoder>>,
+    filters: Vec<Filter>,
+    config: SniperConfig,
+    seen: Option<SeenSet>,
+    recorder: Option<Recorder>,
+}
+
+impl SniperBuilder {
+    pub fn connection(mut self, conn: Connection) -> Self {
+        self.source = Some(Source::Rpc(conn));
+        self
+    }
+
+    pub fn replay(mut self, config: ReplayConfig) -> Self {
+        self.source = Some(Source::Replay(config));
+        self
+    }
+
+    pub fn backfill(mut self, conn: Connection, config: BackfillConfig) -> Self {
+        self.source = Some(Source::Backfill(conn, config));
+        self
+    }
+
+    pub fn source(mut self, source: Source) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn sink(mut self, sink: Arc<dyn SniperAction>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    pub fn decoder(mut self, decoder: Arc<dyn Decoder>) -> Self {
+        self.decoder = Some(decoder);
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn config(mut self, config: SniperConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn dedup(mut self, seen: SeenSet) -> Self {
+        self.seen = Some(seen);
+        self
+    }
+
+    pub fn recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn build(mut self) -> Result<Sniper> {
+        let source = self
+            .source
+            .ok_or_else(|| anyhow::anyhow!("no transaction source configured (connection, replay or backfill)"))?;
+
+        let action: Arc<dyn SniperAction> = match self.sinks.len() {
+            0 => anyhow::bail!("no sinks configured"),
+            1 => self.sinks.remove(0),
+            _ => Arc::new(FanOut::new(self.sinks)),
+        };
+
+        Ok(Sniper {
+            source,
+            shared: Arc::new(Shared {
+                action,
+                decoder: self.decoder.unwrap_or_else(|| Arc::new(SelectorDecoder)),
+                filters: self.filters,
+                config: self.config,
+                recorder: self.recorder.map(Arc::new),
+                seen: Mutex::new(self.seen.unwrap_or_else(|| {
+                    SeenSet::new(dedup::DEFAULT_CAPACITY, Duration::from_secs(dedup::DEFAULT_TTL_SECS))
+                })),
+                shutdown: Shutdown::new(),
+            }),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SniperHandle {
+    shutdown: Shutdown,
+}
+
+impl SniperHandle {
+    pub fn shutdown(&self) {
+        self.shutdown.trigger();
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.triggered_at().is_some()
+    }
+}
+
+pub struct Sniper {
+    source: Source,
+    shared: Arc<Shared>,
+}
+
+impl Sniper {
+    pub fn builder() -> SniperBuilder {
+        SniperBuilder::default()
+    }
+
+    pub fn handle(&self) -> SniperHandle {
+        SniperHandle {
+            shutdown: self.shared.shutdown.clone(),
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let Sniper { source, shared } = self;
+        let shutdown = shared.shutdown.clone();
+        let drain_timeout = shared.config.drain_timeout;
+
+        let queue = shared.config.queue.clone();
+        info!(
+            "ingest queue: capacity {}, overflow policy {}{}",
+            queue.capacity,
+            queue.policy.name(),
+            if queue.priority { ", highest value first" } else { "" }
+        );
+        let name = shared.config.name.clone();
+        metrics().channel_capacity.with_label(&name).set(queue.capacity as u64);
+        let (tx, rx) = overflow::channel_with_depth::<PendingTx>(queue, metrics().channel_depth.with_label(&name));
+
+        if let Some(recorder) = &shared.recorder {
+            info!("recording fetched transactions to {}", recorder.path().display());
+        }
+
+        shared.action.start().await?;
+
+        info!("spawning ingestor and consumer tasks...");
+
+        let mut ingestor_handle = match source {
+            Source::Rpc(conn) => supervisor::supervise(&name, "ingestor", shutdown.clone(), {
+                let shared = shared.clone();
+                let limiter = AdaptiveLimiter::new(shared.config.concurrency.clone());
+                let retries = Arc::new(RetryQueue::new(shared.config.retry.clone()));
+                move || spawn_ingestor(tx.clone(), conn.clone(), limiter.clone(), retries.clone(), shared.clone())
+            }),
+            Source::Replay(config) => supervisor::supervise(&name, "ingestor", shutdown.clone(), {
+                let shared = shared.clone();
+                move || spawn_replay(tx.clone(), config.clone(), shared.clone())
+            }),
+            Source::Backfill(conn, config) => supervisor::supervise(&name, "ingestor", shutdown.clone(), {
+                let shared = shared.clone();
+                move || spawn_backfill(tx.clone(), conn.clone(), config.clone(), shared.clone())
+            }),
+        };
+
+        let mut consumer_handle = supervisor::supervise(&name, "consumer", shutdown.clone(), {
+            let shared = shared.clone();
+            move || spawn_consumer(rx.clone(), shared.clone())
+        });
+
+        let mut ingestor_finished = false;
+        let mut failed = None;
+        let terminated = loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.wait() => {
+                    info!("shutting down (draining for up to {:?})", drain_timeout);
+                    break None;
+                }
+                result = &mut ingestor_handle, if !ingestor_finished => match result {
+                    Ok(result) => {
+                        ingestor_finished = true;
+                        failed = result.err();
+                    }
+                    Err(_) => break Some("ingestor"),
+                },
+                result = &mut consumer_handle => match result {
+                    Ok(result) => {
+                        info!("input finished and drained - shutting down");
+                        failed = failed.or(result.err());
+                        break None;
+                    }
+                    Err(_) => break Some("consumer"),
+                },
+            }
+        };
+
+        if let Some(stage) = terminated {
+            tracing::error!("{} supervisor terminated unexpectedly", stage);
+        }
+
+        shutdown.trigger();
+
+        let deadline = shutdown.deadline(DrainStage::Actions.budget(drain_timeout));
+        for (name, handle) in [("ingestor", &mut ingestor_handle), ("consumer", &mut consumer_handle)] {
+            if handle.is_finished() {
+                continue;
+            }
+            if tokio::time::timeout_at(deadline, handle).await.is_err() {
+                tracing::warn!("{} did not stop before the drain deadline", name);
+            }
+        }
+
+        if let Some(recorder) = &shared.recorder {
+            recorder.flush().await;
+        }
+
+        info!("flushing sinks");
+        let remaining = shutdown
+            .deadline(DrainStage::Flush.budget(drain_timeout))
+            .saturating_duration_since(tokio::time::Instant::now());
+        if let Err(e) = shared.action.flush(remaining).await {
+            tracing::warn!("failed to flush pending alerts: {}", e);
+        }
+
+        for stage in ["ingestor", "consumer"] {
+            if let Some(health) = metrics().stage_health(&name, stage).filter(|h| h.restarts > 0) {
+                tracing::warn!(
+                    "{} restarted {} times (last: {})",
+                    stage,
+                    health.restarts,
+                    health.last_error.unwrap_or_default()
+                );
+            }
+        }
+
+        if let Some(stage) = terminated {
+            anyhow::bail!("{} supervisor terminated unexpectedly", stage);
+        }
+
+        match failed {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+
+async fn spawn_ingestor(
+    tx_channel: overflow::Sender<PendingTx>,
+    conn: Connection,
+    limiter: Arc<AdaptiveLimiter>,
+    retries: Arc<RetryQueue>,
+    shared: Arc<Shared>,
+) -> StageExit {
+    info!("ingestor started");
+
+    let config = &shared.config;
+    let shutdown = &shared.shutdown;
+    let ctx = FetchContext {
+        provider: conn.provider.clone(),
+        tx_channel,
+        limiter,
+        retries,
+        budget: config.budget.clone(),
+        cu_per_fetch: config.cu_per_fetch,
+        recorder: shared.recorder.clone(),
+        shared: shared.clone(),
+    };
+    let batch = match conn.kind {
+        TransportKind::Http => config.batch.clone(),
+        _ => BatchConfig {
+            max_size: 1,
+            ..config.batch.clone()
+        },
+    };
+    let mut batcher = Batcher::new(batch);
+    let mut attempt = 0u32;
+
+    'subscription: loop {
+        let opened = tokio::select! {
+            _ = shutdown.wait() => break 'subscription,
+            opened = ingestor::HashStream::open(&conn) => opened,
+        };
+
+        let mut sub = match opened {
+            Ok(sub) => {
+                info!("subscribed to pending transactions ({})", conn.kind);
+                sub
+            }
+            Err(err) => {
+                metrics().reconnects.inc();
+                tracing::error!("subscription error: {}", err);
+
+                let delay = err.retry_delay(attempt);
+                attempt = attempt.saturating_add(1);
+
+                tracing::warn!("retrying subscription in {:?} (attempt {})...", delay, attempt);
+                tokio::select! {
+                    _ = shutdown.wait() => break 'subscription,
+                    _ = tokio::time::sleep(delay) => continue,
+                }
+            }
+        };
+
+        if config.full_capture {
+            info!("full capture mode: processing 100% of all transactions (zero filtering)");
+        } else {
+            info!("selective mode: processing transactions the decoder selects");
+        }
+
+        let mut tx_count = 0u64;
+        let mut duplicate_count = 0u64;
+        
+        loop {
+            let deadline = batcher.deadline();
+            let received = tokio::select! {
+                _ = shutdown.wait() => break 'subscription,
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(tokio::time::Instant::now)), if deadline.is_some() => {
+                    dispatch_batch(batcher.take(), &ctx).await;
+                    continue;
+                }
+                _ = ctx.retries.wait_due() => {
+                    for fetch in ctx.retries.take_due() {
+                        if let Some(batch) = batcher.push(fetch) {
+                            dispatch_batch(batch, &ctx).await;
+                        }
+                    }
+                    continue;
+                }
+                received = sub.recv() => received,
+            };
+
+            match received {
+                Ok(tx_hash) => {
+                    let timings = Timings::start();
+                    attempt = 0;
+                    metrics().hashes_received.inc();
+
+                    if !shared.seen.lock().unwrap().insert(tx_hash) {
+                        duplicate_count += 1;
+                        tracing::debug!("tx: {} | already seen, skipping ({} duplicates)", tx_hash, duplicate_count);
+                        continue;
+                    }
+
+                    tx_count += 1;
+                    
+                    if tx_count.is_multiple_of(10) {
+                        tracing::debug!("scanned {} transactions...", tx_count);
+                    }
+                    
+                    if tx_count.is_multiple_of(100) {
+                        tracing::info!("processed {} transactions ({} hashes tracked)", tx_count, shared.seen.lock().unwrap().tracked());
+                    }
+
+                    if let Some(batch) = batcher.push(PendingFetch::new(tx_hash, timings)) {
+                        dispatch_batch(batch, &ctx).await;
+                    }
+                }
+                Err(err) => {
+                    metrics().reconnects.inc();
+
+                    match err {
+                        IngestError::Lagged(_) => {
+                            tracing::info!("{} - reconnecting", err);
+                        }
+                        _ => {
+                            tracing::error!("{} - reconnecting", err);
+                        }
+                    }
+
+                    let delay = err.retry_delay(attempt);
+                    attempt = attempt.saturating_add(1);
+
+                    if !delay.is_zero() {
+                        tracing::warn!("waiting {:?} before resubscribing", delay);
+                        tokio::select! {
+                            _ = shutdown.wait() => break 'subscription,
+                            _ = tokio::time::sleep(delay) => {}
+                        }
+                    }
+
+                    break;
+                }
+            }
+        }
+    }
+
+    let pending = batcher.take();
+    if !pending.is_empty() {
+        dispatch_batch(pending, &ctx).await;
+    }
+
+    let abandoned = ctx.retries.depth();
+    if abandoned > 0 {
+        metrics().fetch_not_found.add(abandoned as u64);
+        tracing::debug!("abandoning {} not found hashes awaiting retry", abandoned);
+    }
+
+    info!("ingestor stopped - waiting for in-flight fetches");
+
+    let abandoned = ctx.limiter.drain(shutdown.deadline(DrainStage::Queue.budget(config.drain_timeout))).await;
+    if abandoned > 0 {
+        tracing::warn!("drain deadline reached - abandoning {} in-flight fetches", abandoned);
+    }
+
+    ctx.tx_channel.close();
+    StageExit::Stopped
+}
+
+#[derive(Clone)]
+struct FetchContext {
+    provider: SniperProvider,
+    tx_channel: overflow::Sender<PendingTx>,
+    limiter: Arc<AdaptiveLimiter>,
+    retries: Arc<RetryQueue>,
+    budget: Option<Arc<ComputeBudget>>,
+    cu_per_fetch: u32,
+    recorder: Option<Arc<Recorder>>,
+    shared: Arc<Shared>,
+}
+
+async fn dispatch_batch(mut batch: Vec<PendingFetch>, ctx: &FetchContext) {
+    while !batch.is_empty() {
+        if ctx.limiter.available() == 0 {
+            metrics().semaphore_saturated.inc();
+        }
+
+        let mut permits = vec![ctx.limiter.acquire().await];
+        while permits.len() < batch.len() {
+            match ctx.limiter.try_acquire() {
+                Some(permit) => permits.push(permit),
+                None => break,
+            }
+        }
+
+        let chunk: Vec<_> = batch.drain(..permits.len()).collect();
+        spawn_fetch(chunk, permits, ctx.clone());
+    }
+}
+
+fn spawn_fetch(batch: Vec<PendingFetch>, permits: Vec<FetchPermit>, ctx: FetchContext) {
+    supervisor::spawn_task("fetch", async move {
+        let _permits = permits;
+
+        if let Some(budget) = &ctx.budget {
+            budget.spend(ctx.cu_per_fetch.saturating_mul(batch.len() as u32)).await;
+        }
+
+        let hashes: Vec<_> = batch.iter().map(|fetch| fetch.hash).collect();
+        let started_at = std::time::Instant::now();
+        let fetched = tokio::time::timeout(
+            ctx.limiter.fetch_timeout(),
+            batch::fetch_transactions(&ctx.provider, &hashes),
+        )
+        .await;
+
+        let results = match fetched {
+            Ok(Ok(results)) => results,
+            Ok(Err(e)) => {
+                let err = IngestError::from(e);
+                match err {
+                    IngestError::RateLimited => tracing::debug!("batch of {} hashes rate limited", batch.len()),
+                    _ => tracing::warn!("batch of {} hashes failed: {}", batch.len(), err),
+                }
+                let outcome = record_fetch_error(&err, batch.len() as u64);
+                for fetch in batch {
+                    ctx.limiter.record(outcome);
+                    if outcome == FetchOutcome::RateLimited {
+                        retry_fetch(&ctx, fetch, "rate limited");
+                    }
+                }
+                return;
+            }
+            Err(_) => {
+                metrics().fetch_timeout.add(batch.len() as u64);
+                tracing::debug!("batch of {} hashes timed out after {:?}", batch.len(), ctx.limiter.fetch_timeout());
+                for fetch in batch {
+                    ctx.limiter.record(FetchOutcome::Timeout);
+                    retry_fetch(&ctx, fetch, "timed out");
+                }
+                return;
+            }
+        };
+
+        let latency = started_at.elapsed();
+
+        for (fetch, result) in batch.into_iter().zip(results) {
+            let tx_hash = fetch.hash;
+
+            let fetched = match result {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    let err = IngestError::from(e);
+                    match err {
+                        IngestError::RateLimited => tracing::debug!("tx: {} | fetch rate limited", tx_hash),
+                        _ => tracing::warn!("tx: {} | error fetching: {}", tx_hash, err),
+                    }
+                    let outcome = record_fetch_error(&err, 1);
+                    ctx.limiter.record(outcome);
+                    if outcome == FetchOutcome::RateLimited {
+                        retry_fetch(&ctx, fetch, "rate limited");
+                    }
+                    continue;
+                }
+            };
+
+            ctx.limiter.record(FetchOutcome::Ok(latency));
+
+            match fetched {
+                Some(tx) => {
+                    let mut timings = fetch.timings;
+                    timings.fetched_at = Some(std::time::Instant::now());
+                    metrics().fetch_ok.inc();
+                    retry::record_found(&fetch);
+
+                    if let Some(recorder) = &ctx.recorder {
+                        recorder.record(&tx, std::time::SystemTime::now() - fetch.timings.received_at.elapsed());
+                    }
+                    let input_data = tx.inner.input();
+
+                    if !ctx.shared.selects(input_data) {
+                        continue;
+                    }
+
+                    tracing::debug!("target selector detected in mempool: {}", tx_hash);
+
+                    let mut pending = PendingTx::new(tx, timings);
+                    let value = pending.priority(&*ctx.shared.decoder, ctx.tx_channel.ranks_by_value());
+
+                    match ctx.tx_channel.send(pending, value).await {
+                        overflow::SendOutcome::Queued => {}
+                        overflow::SendOutcome::Evicted => {
+                            tracing::debug!("buffer full - evicted a queued tx for {}", tx_hash);
+                        }
+                        overflow::SendOutcome::Dropped => {
+                            tracing::warn!("buffer full - dropping target tx: {}", tx_hash);
+                        }
+                        overflow::SendOutcome::TimedOut => {
+                            tracing::warn!("buffer full - timed out queueing target tx: {}", tx_hash);
+                        }
+                        overflow::SendOutcome::Closed => {
+                            tracing::debug!("queue closed - dropping target tx: {}", tx_hash);
+                        }
+                    }
+                }
+                None => match ctx.retries.schedule(fetch) {
+                    RetryDecision::Scheduled(delay) => {
+                        tracing::debug!("tx: {} | not found, retrying in {:?} (attempt {})", tx_hash, delay, fetch.attempt + 1);
+                    }
+                    RetryDecision::Exhausted => {
+                        metrics().fetch_not_found.inc();
+                        tracing::debug!("tx: {} | not found after {} attempts", tx_hash, fetch.attempt + 1);
+                    }
+                    RetryDecision::Overflow => {
+                        metrics().fetch_not_found.inc();
+                        tracing::debug!("tx: {} | not found, retry queue full", tx_hash);
+                    }
+                },
+            }
+        }
+    });
+}
+
+fn retry_fetch(ctx: &FetchContext, fetch: PendingFetch, reason: &str) {
+    match ctx.retries.schedule(fetch) {
+        RetryDecision::Scheduled(delay) => {
+            tracing::debug!("tx: {} | {}, retrying in {:?} (attempt {})", fetch.hash, reason, delay, fetch.attempt + 1);
+        }
+        RetryDecision::Exhausted => {
+            tracing::debug!("tx: {} | {} after {} attempts, giving up", fetch.hash, reason, fetch.attempt + 1);
+        }
+        RetryDecision::Overflow => {
+            tracing::debug!("tx: {} | {}, retry queue full", fetch.hash, reason);
+        }
+    }
+}
+
+fn record_fetch_error(err: &IngestError, hashes: u64) -> FetchOutcome {
+    match err {
+        IngestError::RateLimited => {
+            metrics().fetch_rate_limited.add(hashes);
+            FetchOutcome::RateLimited
+        }
+        _ => {
+            metrics().fetch_error.add(hashes);
+            FetchOutcome::Error
+        }
+    }
+}
+
+async fn spawn_replay(tx_channel: overflow::Sender<PendingTx>, config: ReplayConfig, shared: Arc<Shared>) -> StageExit {
+    info!("replaying {}", config.path.display());
+
+    let shutdown = &shared.shutdown;
+    match replay::replay(&config, &tx_channel, &*shared.decoder, shutdown).await {
+        Ok(stats) => info!(
+            "replay finished: {} transactions replayed, {} unreadable lines skipped",
+            stats.replayed, stats.skipped
+        ),
+        Err(e) => {
+            tx_channel.close();
+            return StageExit::Failed(e.context("replay failed"));
+        }
+    }
+
+    tx_channel.close();
+    StageExit::Finished
+}
+
+async fn spawn_backfill(
+    tx_channel: overflow::Sender<PendingTx>,
+    conn: Connection,
+    config: BackfillConfig,
+    shared: Arc<Shared>,
+) -> StageExit {
+    let shutdown = &shared.shutdown;
+    match backfill::backfill(&conn.provider, &config, &tx_channel, &*shared.decoder, shutdown).await {
+        Ok(stats) => info!(
+            "backfill finished: {} blocks, {} transactions, {} missing blocks skipped",
+            stats.blocks, stats.transactions, stats.missing
+        ),
+        Err(e) => {
+            tx_channel.close();
+            return StageExit::Failed(e.context("backfill failed"));
+        }
+    }
+
+    tx_channel.close();
+    StageExit::Finished
+}
+
+async fn spawn_consumer(rx_channel: overflow::Receiver<PendingTx>, shared: Arc<Shared>) -> StageExit {
+    info!("consumer started");
+    if shared.filters.is_empty() {
+        info!("full capture mode: processing all transactions (no value threshold)");
+    } else {
+        let sources: Vec<_> = shared.filters.iter().map(|filter| filter.source()).collect();
+        info!("filtering transactions: {}", sources.join(" && "));
+    }
+
+    let max_actions = shared.config.max_concurrent_actions.max(1);
+    let semaphore = Arc::new(Semaphore::new(max_actions));
+    info!("parallel consumer engine: {} concurrent workers", max_actions);
+
+    let shutdown = &shared.shutdown;
+    let drain_expired = shutdown.expired(DrainStage::Queue.budget(shared.config.drain_timeout));
+    tokio::pin!(drain_expired);
+    let mut exit = StageExit::Stopped;
+
+    loop {
+        let received = tokio::select! {
+            received = rx_channel.recv() => received,
+            _ = &mut drain_expired => {
+                tracing::warn!("drain deadline reached - abandoning {} queued transactions", rx_channel.depth());
+                break;
+            }
+        };
+
+        let pending = match received {
+            Some(pending) => pending,
+            None => {
+                info!("consumer stopped - channel closed");
+                exit = StageExit::Finished;
+                break;
+            }
+        };
+
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(p) => p,
+            Err(_) => {
+                tracing::error!("semaphore closed");
+                break;
+            }
+        };
+
+        let shared_clone = shared.clone();
+
+        supervisor::spawn_task("action", async move {
+            let _permit = permit;
+
+            let PendingTx { tx, mut timings, decoded } = pending;
+            let tx_hash = tx.inner.tx_hash();
+            let input_data = tx.inner.input();
+            let tx_value = tx.inner.value();
+
+            if !shared_clone.selects(input_data) {
+                tracing::debug!("tx: {} | not selected by the decoder", tx_hash);
+                return;
+            }
+
+            tracing::info!("processing tx: {:?}", tx_hash);
+
+            let decoded = match decoded.map_or_else(|| shared_clone.decoder.decode(input_data, tx_value), Ok) {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    metrics().record_decode("decode_error");
+                    tracing::debug!("failed to decode tx {}: {}", tx_hash, e);
+                    return;
+                }
+            };
+
+            timings.decoded_at = Some(std::time::Instant::now());
+            metrics().record_decode(&decoded.method);
+
+            let detected_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64;
+            
+            let mut target_tx = TargetTransaction {
+                tx_hash: *tx_hash,
+                from: tx.from,
+                value: decoded.effective_value,
+                method: decoded.method,
+                amount_out_min: decoded.amount_out_min,
+                path: decoded.path,
+                to: decoded.to,
+                deadline: decoded.deadline,
+                detected_at,
+                block_number: tx.block_number,
+                timings,
+            };
+
+            let dispatched_at = std::time::Instant::now();
+            target_tx.timings.dispatched_at = Some(dispatched_at);
+
+            if !shared_clone.filters.iter().all(|filter| filter.matches(&target_tx)) {
+                metrics().filtered.inc();
+                tracing::debug!("tx: {} | filtered out", tx_hash);
+                return;
+            }
+
+            let result = shared_clone.action.execute(&target_tx).await;
+
+            let completed_at = std::time::Instant::now();
+            target_tx.timings.completed_at = Some(completed_at);
+            metrics().observe_action(shared_clone.action.name(), completed_at - dispatched_at);
+            metrics().observe_timings(&target_tx.timings);
+
+            if let Err(e) = result {
+                tracing::error!("failed to execute action for tx {}: {}", tx_hash, e);
+            }
+        });
+    }
+
+    let abandoned = tokio::select! {
+        biased;
+        _ = semaphore.acquire_many(max_actions as u32) => 0,
+        _ = shutdown.expired(DrainStage::Actions.budget(shared.config.drain_timeout)) => max_actions.saturating_sub(semaphore.available_permits()),
+    };
+    if abandoned > 0 {
+        tracing::warn!("drain deadline reached - abandoning {} in-flight actions", abandoned);
+    }
+
+    exit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder;
+    use crate::fixtures::rpc_tx;
+    use crate::mock_node::MockNode;
+    use crate::overflow::OverflowPolicy;
+    use alloy::primitives::{B256, U256};
+
+    #[derive(Default)]
+    struct RecordingAction {
+        executed: Mutex<Vec<TargetTransaction>>,
+        changed: tokio::sync::Notify,
+    }
+
+    #[async_trait::async_trait]
+    impl SniperAction for RecordingAction {
+        async fn execute(&self, tx: &TargetTransaction) -> anyhow::Result<()> {
+            self.executed.lock().unwrap().push(tx.clone());
+            self.changed.notify_waiters();
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+    }
+
+    impl RecordingAction {
+        fn hashes(&self) -> Vec<B256> {
+            self.executed.lock().unwrap().iter().map(|tx| tx.tx_hash).collect()
+        }
+
+        async fn wait_for(&self, hash: B256) -> TargetTransaction {
+            let wait = async {
+                loop {
+                    let changed = self.changed.notified();
+                    if let Some(tx) = self.executed.lock().unwrap().iter().find(|tx| tx.tx_hash == hash) {
+                        return tx.clone();
+                    }
+                    changed.await;
+                }
+            };
+            tokio::time::timeout(Duration::from_secs(10), wait)
+                .await
+                .unwrap_or_else(|_| panic!("tx {} never reached the action", hash))
+        }
+    }
+
+    struct Pipeline {
+        action: Arc<RecordingAction>,
+        handle: SniperHandle,
+        running: tokio::task::JoinHandle<Result<()>>,
+    }
+
+    impl Pipeline {
+        async fn start(node: &MockNode) -> Self {
+            Self::start_with(node, |builder| builder).await
+        }
+
+        async fn start_with(node: &MockNode, configure: impl FnOnce(SniperBuilder) -> SniperBuilder) -> Self {
+            let conn = ingestor::establish_connection(&node.url).await.unwrap();
+            let action = Arc::new(RecordingAction::default());
+            let builder = Sniper::builder()
+                .connection(conn)
+                .sink(action.clone())
+                .dedup(SeenSet::new(10_000, Duration::from_secs(60)))
+                .config(SniperConfig {
+                    queue: QueueConfig {
+                        capacity: 10_000,
+                        ..QueueConfig::default()
+                    },
+                    drain_timeout: Duration::from_secs(1),
+                    ..SniperConfig::default()
+                });
+            let sniper = configure(builder).build().unwrap();
+            let handle = sniper.handle();
+            let running = tokio::spawn(sniper.run());
+
+            wait_until(|| node.calls("eth_subscribe") > 0).await;
+
+            Self { action, handle, running }
+        }
+
+        async fn stop(self) -> Arc<RecordingAction> {
+            self.handle.shutdown();
+            self.running.await.unwrap().unwrap();
+            self.action
+        }
+    }
+
+    async fn wait_until(condition: impl Fn() -> bool) {
+        let wait = async {
+            while !condition() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), wait)
+            .await
+            .expect("condition not met in time");
+    }
+
+    async fn announce_until(node: &MockNode, hashes: &[B256], condition: impl Fn() -> bool) {
+        let announce = async {
+            loop {
+                node.announce(hashes);
+                for _ in 0..10 {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    if condition() {
+                        return;
+                    }
+                }
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), announce)
+            .await
+            .expect("announced hashes never took effect");
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_delivers_announced_transactions() {
+        let node = MockNode::start().await;
+        let first = node.add_tx(rpc_tx(1, 1_000_000_000_000_000_000));
+        let second = node.add_tx(rpc_tx(2, 5));
+        let pipeline = Pipeline::start(&node).await;
+
+        announce_until(&node, &[first, second, first], || pipeline.action.hashes().len() >= 2).await;
+
+        let delivered = pipeline.action.wait_for(first).await;
+        assert_eq!(delivered.value, U256::from(1_000_000_000_000_000_000u128));
+        assert_eq!(delivered.method, "Native Transfer");
+        pipeline.action.wait_for(second).await;
+
+        let action = pipeline.stop().await;
+        assert_eq!(action.hashes().len(), 2, "repeated announcements are processed once");
+    }
+
+    #[derive(Default)]
+    struct StuckAction {
+        started: tokio::sync::Notify,
+        flush_timeout: Mutex<Option<Duration>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SniperAction for StuckAction {
+        async fn execute(&self, _tx: &TargetTransaction) -> anyhow::Result<()> {
+            self.started.notify_one();
+            std::future::pending().await
+        }
+
+        async fn flush(&self, timeout: Duration) -> anyhow::Result<()> {
+            *self.flush_timeout.lock().unwrap() = Some(timeout);
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "stuck"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_splits_one_drain_budget() {
+        let node = MockNode::start().await;
+        let stuck = node.add_tx(rpc_tx(8, 1));
+        let conn = ingestor::establish_connection(&node.url).await.unwrap();
+        let action = Arc::new(StuckAction::default());
+        let drain_timeout = Duration::from_millis(800);
+
+        let sniper = Sniper::builder()
+            .connection(conn)
+            .sink(action.clone())
+            .config(SniperConfig {
+                drain_timeout,
+                ..SniperConfig::default()
+            })
+            .build()
+            .unwrap();
+        let handle = sniper.handle();
+        let running = tokio::spawn(sniper.run());
+        wait_until(|| node.calls("eth_subscribe") > 0).await;
+
+        let started = action.started.notified();
+        node.announce(&[stuck]);
+        tokio::time::timeout(Duration::from_secs(10), started).await.unwrap();
+
+        tokio::time::pause();
+        let stopping = tokio::time::Instant::now();
+        handle.shutdown();
+        running.await.unwrap().unwrap();
+
+        assert!(stopping.elapsed() <= drain_timeout, "took {:?}", stopping.elapsed());
+        let flush_timeout = action.flush_timeout.lock().unwrap().expect("sinks are flushed");
+        assert!(flush_timeout > Duration::from_millis(100), "flush got {:?}", flush_timeout);
+        assert!(flush_timeout <= drain_timeout / 4);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_retries_not_found_transactions() {
+        let node = MockNode::start().await;
+        let late = node.add_tx(rpc_tx(3, 1));
+        node.hide(late, 2);
+        let pipeline = Pipeline::start(&node).await;
+
+        announce_until(&node, &[late], || node.calls("eth_getTransactionByHash") > 0).await;
+
+        pipeline.action.wait_for(late).await;
+        assert_eq!(node.calls("eth_getTransactionByHash"), 3);
+        pipeline.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_retries_rate_limited_fetches() {
+        let node = MockNode::start().await;
+        let limited = node.add_tx(rpc_tx(4, 1));
+        let next = node.add_tx(rpc_tx(5, 1));
+        node.rate_limit(1);
+        let pipeline = Pipeline::start(&node).await;
+
+        announce_until(&node, &[limited], || node.calls("eth_getTransactionByHash") > 0).await;
+        announce_until(&node, &[next], || pipeline.action.hashes().contains(&next)).await;
+        pipeline.action.wait_for(limited).await;
+
+        pipeline.stop().await;
+        assert!(node.calls("eth_getTransactionByHash") >= 3);
+        assert!(metrics().fetch_rate_limited.get() >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_recovers_from_disconnect() {
+        let node = MockNode::start().await;
+        let before = node.add_tx(rpc_tx(6, 1));
+        let after = node.add_tx(rpc_tx(7, 1));
+        let pipeline = Pipeline::start(&node).await;
+
+        announce_until(&node, &[before], || pipeline.action.hashes().contains(&before)).await;
+
+        node.disconnect();
+        wait_until(|| node.connections() >= 2 && node.calls("eth_subscribe") >= 2).await;
+        announce_until(&node, &[after], || pipeline.action.hashes().contains(&after)).await;
+
+        pipeline.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_resubscribes_after_lag() {
+        let node = MockNode::start().await;
+        let warmup = node.add_tx(rpc_tx(8, 1));
+        let burst: Vec<_> = (100..2_100).map(|n| node.add_tx(rpc_tx(n, 1))).collect();
+        let marker = node.add_tx(rpc_tx(9, 1));
+        let pipeline = Pipeline::start(&node).await;
+
+        announce_until(&node, &[warmup], || pipeline.action.hashes().contains(&warmup)).await;
+
+        node.announce(&burst);
+        wait_until(|| node.calls("eth_subscribe") >= 2).await;
+        announce_until(&node, &[marker], || pipeline.action.hashes().contains(&marker)).await;
+
+        let action = pipeline.stop().await;
+        assert!(action.hashes().len() < burst.len() + 2, "lagged hashes are skipped");
+    }
+
+    struct FixedDecoder;
+
+    impl Decoder for FixedDecoder {
+        fn decode(&self, _input_data: &[u8], tx_value: U256) -> Result<decoder::DecodedTx> {
+            Ok(decoder::DecodedTx {
+                amount_out_min: U256::ZERO,
+                path: vec![],
+                to: alloy::primitives::Address::ZERO,
+                deadline: U256::ZERO,
+                effective_value: tx_value * U256::from(2),
+                method: "custom".to_string(),
+            })
+        }
+    }
+
+    struct CallsOnlyDecoder;
+
+    impl Decoder for CallsOnlyDecoder {
+        fn decode(&self, input_data: &[u8], tx_value: U256) -> Result<decoder::DecodedTx> {
+            FixedDecoder.decode(input_data, tx_value)
+        }
+
+        fn selects(&self, input_data: &[u8]) -> bool {
+            !input_data.is_empty()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decoder_selects_transactions_without_full_capture() {
+        let path = std::env::temp_dir().join(format!("sniper-pipeline-select-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = Recorder::create(&path, 16).unwrap();
+        let transfer: alloy::rpc::types::Transaction = serde_json::from_value(rpc_tx(30, 1)).unwrap();
+        let mut call = rpc_tx(31, 1);
+        call["input"] = "0xdeadbeef".into();
+        let call: alloy::rpc::types::Transaction = serde_json::from_value(call).unwrap();
+        recorder.record(&transfer, std::time::SystemTime::now());
+        recorder.record(&call, std::time::SystemTime::now());
+        recorder.flush().await;
+
+        let run = |full_capture| {
+            let action = Arc::new(RecordingAction::default());
+            let sniper = Sniper::builder()
+                .replay(ReplayConfig { path: path.clone(), speed: 0.0 })
+                .decoder(Arc::new(CallsOnlyDecoder))
+                .sink(action.clone())
+                .config(SniperConfig {
+                    full_capture,
+                    ..SniperConfig::default()
+                })
+                .build()
+                .unwrap();
+            async move {
+                sniper.run().await.unwrap();
+                let mut hashes = action.hashes();
+                hashes.sort();
+                hashes
+            }
+        };
+
+        assert_eq!(run(false).await, vec![*call.inner.tx_hash()]);
+        assert_eq!(run(true).await.len(), 2, "full capture ignores the decoder's selection");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[derive(Default)]
+    struct CountingDecoder(std::sync::atomic::AtomicUsize);
+
+    impl Decoder for CountingDecoder {
+        fn decode(&self, input_data: &[u8], tx_value: U256) -> Result<decoder::DecodedTx> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            FixedDecoder.decode(input_data, tx_value)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_each_transaction_is_decoded_once() {
+        let path = std::env::temp_dir().join(format!("sniper-pipeline-decode-once-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = Recorder::create(&path, 16).unwrap();
+        for id in 40..43 {
+            let tx: alloy::rpc::types::Transaction = serde_json::from_value(rpc_tx(id, 1)).unwrap();
+            recorder.record(&tx, std::time::SystemTime::now());
+        }
+        recorder.flush().await;
+
+        for policy in [OverflowPolicy::DropNewest, OverflowPolicy::DropLowestValue] {
+            let decoder = Arc::new(CountingDecoder::default());
+            let action = Arc::new(RecordingAction::default());
+            Sniper::builder()
+                .replay(ReplayConfig { path: path.clone(), speed: 0.0 })
+                .decoder(decoder.clone())
+                .sink(action.clone())
+                .config(SniperConfig {
+                    queue: QueueConfig {
+                        policy,
+                        ..QueueConfig::default()
+                    },
+                    ..SniperConfig::default()
+                })
+                .build()
+                .unwrap()
+                .run()
+                .await
+                .unwrap();
+
+            assert_eq!(action.hashes().len(), 3);
+            assert_eq!(decoder.0.load(std::sync::atomic::Ordering::SeqCst), 3, "{:?}", policy);
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_named_pipelines_keep_separate_stage_metrics() {
+        let path = std::env::temp_dir().join(format!("sniper-pipeline-named-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        std::fs::write(&path, "").unwrap();
+
+        for (name, capacity) in [("named-a", 10), ("named-b", 20)] {
+            Sniper::builder()
+                .replay(ReplayConfig { path: path.clone(), speed: 0.0 })
+                .sink(Arc::new(RecordingAction::default()))
+                .config(SniperConfig {
+                    name: name.to_string(),
+                    queue: QueueConfig {
+                        capacity,
+                        ..QueueConfig::default()
+                    },
+                    ..SniperConfig::default()
+                })
+                .build()
+                .unwrap()
+                .run()
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(metrics().channel_capacity.get("named-a"), 10);
+        assert_eq!(metrics().channel_capacity.get("named-b"), 20);
+        for name in ["named-a", "named-b"] {
+            for stage in ["ingestor", "consumer"] {
+                let health = metrics().stage_health(name, stage).unwrap();
+                assert!(!health.up);
+                assert_eq!(health.restarts, 0);
+            }
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_builder_requires_source_and_sink() {
+        let missing_source = Sniper::builder().sink(Arc::new(RecordingAction::default())).build();
+        assert!(missing_source.is_err());
+
+        let replay = ReplayConfig {
+            path: std::env::temp_dir().join("sniper-unused.jsonl"),
+            speed: 0.0,
+        };
+        assert!(Sniper::builder().replay(replay.clone()).build().is_err());
+        assert!(Sniper::builder()
+            .replay(replay)
+            .sink(Arc::new(RecordingAction::default()))
+            .build()
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_applies_custom_decoder_and_filters() {
+        let node = MockNode::start().await;
+        let small = node.add_tx(rpc_tx(10, 1));
+        let large = node.add_tx(rpc_tx(11, 1_000_000_000_000_000_000));
+        let filtered_before = metrics().filtered.get();
+        let pipeline = Pipeline::start_with(&node, |builder| {
+            builder
+                .decoder(Arc::new(FixedDecoder))
+                .filter(Filter::parse("method == custom").unwrap())
+                .filter(Filter::parse("value > 1").unwrap())
+        })
+        .await;
+
+        announce_until(&node, &[small, large], || pipeline.action.hashes().contains(&large)).await;
+        wait_until(|| metrics().filtered.get() > filtered_before).await;
+
+        let delivered = pipeline.action.wait_for(large).await;
+        assert_eq!(delivered.method, "custom");
+        assert_eq!(delivered.value, U256::from(2_000_000_000_000_000_000u128));
+
+        let action = pipeline.stop().await;
+        assert!(!action.hashes().contains(&small), "filtered transactions never reach the sink");
+    }
+
+    #[tokio::test]
+    async fn test_replay_source_finishes_run() {
+        let path = std::env::temp_dir().join(format!("sniper-pipeline-replay-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = Recorder::create(&path, 64).unwrap();
+        let mut expected = Vec::new();
+        for n in 1..=20 {
+            let tx: alloy::rpc::types::Transaction = serde_json::from_value(rpc_tx(n, 7)).unwrap();
+            recorder.record(&tx, std::time::SystemTime::now());
+            expected.push(*tx.inner.tx_hash());
+        }
+        recorder.flush().await;
+
+        let action = Arc::new(RecordingAction::default());
+        let sniper = Sniper::builder()
+            .replay(ReplayConfig { path: path.clone(), speed: 0.0 })
+            .sink(action.clone())
+            .config(SniperConfig {
+                queue: QueueConfig {
+                    capacity: 1,
+                    ..QueueConfig::default()
+                },
+                drain_timeout: Duration::from_millis(1),
+                max_concurrent_actions: 1,
+                ..SniperConfig::default()
+            })
+            .build()
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(10), sniper.run())
+            .await
+            .expect("replay run finishes on its own")
+            .unwrap();
+
+        let mut hashes = action.hashes();
+        hashes.sort();
+        expected.sort();
+        assert_eq!(hashes, expected, "a full queue and a short drain window lose nothing");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_missing_replay_file_fails_run() {
+        let path = std::env::temp_dir().join(format!("sniper-pipeline-missing-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let sniper = Sniper::builder()
+            .replay(ReplayConfig { path, speed: 0.0 })
+            .sink(Arc::new(RecordingAction::default()))
+            .config(SniperConfig {
+                drain_timeout: Duration::from_millis(1),
+                ..SniperConfig::default()
+            })
+            .build()
+            .unwrap();
+
+        let err = tokio::time::timeout(Duration::from_secs(10), sniper.run())
+            .await
+            .expect("failed replay ends the run")
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("replay failed"), "{:#}", err);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_source_flags_mined_transactions() {
+        let node = MockNode::start().await;
+        let mined = node.add_block(20, vec![rpc_tx(13, 1), rpc_tx(14, 2)]);
+        node.add_block(21, vec![rpc_tx(15, 3)]);
+        let conn = ingestor::establish_connection(&node.url).await.unwrap();
+
+        let action = Arc::new(RecordingAction::default());
+        let sniper = Sniper::builder()
+            .backfill(conn, BackfillConfig::new(20, Some(20)))
+            .sink(action.clone())
+            .config(SniperConfig {
+                queue: QueueConfig {
+                    capacity: 1,
+                    ..QueueConfig::default()
+                },
+                drain_timeout: Duration::from_millis(1),
+                ..SniperConfig::default()
+            })
+            .build()
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(10), sniper.run())
+            .await
+            .expect("backfill run finishes on its own")
+            .unwrap();
+
+        let mut hashes = action.hashes();
+        hashes.sort();
+        assert_eq!(hashes, mined);
+        assert_eq!(action.wait_for(mined[0]).await.block_number, Some(20));
+        assert_eq!(node.calls("eth_subscribe"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_backfill_fails_run() {
+        let node = MockNode::start().await;
+        let conn = ingestor::establish_connection(&node.url).await.unwrap();
+
+        let sniper = Sniper::builder()
+            .backfill(conn, BackfillConfig::new(30, Some(20)))
+            .sink(Arc::new(RecordingAction::default()))
+            .config(SniperConfig {
+                drain_timeout: Duration::from_millis(1),
+                ..SniperConfig::default()
+            })
+            .build()
+            .unwrap();
+
+        let err = tokio::time::timeout(Duration::from_secs(10), sniper.run())
+            .await
+            .expect("failed backfill ends the run")
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("backfill range is empty"), "{:#}", err);
+    }
+}
+
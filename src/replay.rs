@@ -0,0 +1,373 @@
+use crate::decoder::Decoder;
+use crate::ingestor::PendingTx;
+use crate::latency::Timings;
+use crate::metrics::metrics;
+use crate::overflow;
+use crate::shutdown::Shutdown;
+use alloy::consensus::Transaction;
+use alloy::eips::eip2718::Encodable2718;
+use alloy::primitives::Bytes;
+use alloy::rpc::types::Transaction as RpcTransaction;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::{mpsc, oneshot};
+
+pub const DEFAULT_RECORD_BUFFER: usize = 10_000;
+pub const DEFAULT_REPLAY_SPEED: f64 = 1.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedTx {
+    pub received_at_ms: u64,
+    pub raw: Bytes,
+    pub tx: RpcTransaction,
+}
+
+impl RecordedTx {
+    pub fn new(tx: RpcTransaction, received_at: SystemTime) -> Self {
+        Self {
+            received_at_ms: received_at
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_millis() as u64)
+                .unwrap_or(0),
+            raw: tx.inner.encoded_2718().into(),
+            tx,
+        }
+    }
+}
+
+enum Entry {
+    Line(String),
+    Flush(oneshot::Sender<()>),
+}
+
+pub struct Recorder {
+    sender: mpsc::Sender<Entry>,
+    path: PathBuf,
+}
+
+impl Recorder {
+    pub fn create(path: impl Into<PathBuf>, buffer: usize) -> Result<Self> {
+        let path = path.into();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open record file {}", path.display()))?;
+
+        let (sender, mut receiver) = mpsc::channel(buffer.max(1));
+        let file_name = path.display().to_string();
+
+        tokio::spawn(async move {
+            let mut writer = BufWriter::new(tokio::fs::File::from_std(file));
+
+            while let Some(entry) = receiver.recv().await {
+                let mut flushed = Vec::new();
+                let mut next = Some(entry);
+
+                while let Some(entry) = next {
+                    match entry {
+                        Entry::Line(line) => {
+                            if let Err(e) = writer.write_all(line.as_bytes()).await {
+                                tracing::error!("failed to write record file {}: {}", file_name, e);
+                            }
+                        }
+                        Entry::Flush(done) => flushed.push(done),
+                    }
+                    next = receiver.try_recv().ok();
+                }
+
+                if let Err(e) = writer.flush().await {
+                    tracing::error!("failed to flush record file {}: {}", file_name, e);
+                }
+                for done in flushed {
+                    let _ = done.send(());
+                }
+            }
+        });
+
+        Ok(Self { sender, path })
+    }
+
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(path) = std::env::var("RECORD_PATH").ok().filter(|p| !p.is_empty()) else {
+            return Ok(None);
+        };
+        let buffer = std::env::var("RECORD_BUFFER")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_RECORD_BUFFER);
+
+        Self::create(path, buffer).map(Some)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, tx: &RpcTransaction, received_at: SystemTime) {
+        let line = match serde_json::to_string(&RecordedTx::new(tx.clone(), received_at)) {
+            Ok(json) => json + "\n",
+            Err(e) => {
+                tracing::warn!("failed to serialize recorded tx {}: {}", tx.inner.tx_hash(), e);
+                return;
+            }
+        };
+
+        match self.sender.try_send(Entry::Line(line)) {
+            Ok(()) => metrics().recorded.inc(),
+            Err(_) => metrics().record_dropped.inc(),
+        }
+    }
+
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.sender.send(Entry::Flush(done)).await.is_ok() {
+            let _ = flushed.await;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub path: PathBuf,
+    pub speed: f64,
+}
+
+impl ReplayConfig {
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("REPLAY_PATH").ok().filter(|p| !p.is_empty())?;
+
+        Some(Self {
+            path: PathBuf::from(path),
+            speed: std::env::var("REPLAY_SPEED")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|speed: &f64| speed.is_finite() && *speed >= 0.0)
+                .unwrap_or(DEFAULT_REPLAY_SPEED),
+        })
+    }
+
+    pub async fn chain_id(&self) -> Result<Option<u64>> {
+        let mut lines = open(&self.path).await?;
+        while let Some(line) = lines.next_line().await? {
+            if let Ok(recorded) = serde_json::from_str::<RecordedTx>(&line) {
+                return Ok(recorded.tx.inner.chain_id());
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub replayed: u64,
+    pub skipped: u64,
+}
+
+pub async fn replay(
+    config: &ReplayConfig,
+    tx_channel: &overflow::Sender<PendingTx>,
+    decoder: &dyn Decoder,
+    shutdown: &Shutdown,
+) -> Result<ReplayStats> {
+    let mut lines = open(&config.path).await?;
+    let mut stats = ReplayStats::default();
+    let mut previous_ms = None;
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let recorded: RecordedTx = match serde_json::from_str(&line) {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                stats.skipped += 1;
+                tracing::warn!("skipping unreadable recorded tx: {}", e);
+                continue;
+            }
+        };
+
+        if let Some(previous_ms) = previous_ms.filter(|_| config.speed > 0.0) {
+            let gap = Duration::from_millis(recorded.received_at_ms.saturating_sub(previous_ms));
+            tokio::select! {
+                _ = shutdown.wait() => break,
+                _ = tokio::time::sleep(gap.div_f64(config.speed)) => {}
+            }
+        }
+        previous_ms = Some(recorded.received_at_ms);
+
+        if shutdown.triggered_at().is_some() {
+            break;
+        }
+
+        let mut timings = Timings::start();
+        timings.fetched_at = Some(timings.received_at);
+        metrics().hashes_received.inc();
+        metrics().fetch_ok.inc();
+
+        let mut pending = PendingTx::new(recorded.tx, timings);
+        let value = pending.priority(decoder, tx_channel.ranks_by_value());
+        let outcome = tokio::select! {
+            biased;
+            _ = shutdown.wait() => break,
+            outcome = tx_channel.send_wait(pending, value) => outcome,
+        };
+        if outcome == overflow::SendOutcome::Closed {
+            break;
+        }
+        stats.replayed += 1;
+    }
+
+    Ok(stats)
+}
+
+async fn open(path: &Path) -> Result<tokio::io::Lines<BufReader<tokio::fs::File>>> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("failed to open replay file {}", path.display()))?;
+    Ok(BufReader::new(file).lines())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::SelectorDecoder;
+    use crate::overflow::{OverflowPolicy, QueueConfig};
+    use crate::chain;
+    use crate::fixtures;
+
+    fn rpc_tx(n: u8, value_wei: u64) -> RpcTransaction {
+        serde_json::from_value(fixtures::rpc_tx_on(chain::SEPOLIA, n as u64, value_wei)).unwrap()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sniper-{}-{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn queue() -> (overflow::Sender<PendingTx>, overflow::Receiver<PendingTx>) {
+        queue_with_capacity(100)
+    }
+
+    fn queue_with_capacity(capacity: usize) -> (overflow::Sender<PendingTx>, overflow::Receiver<PendingTx>) {
+        overflow::channel(QueueConfig {
+            capacity,
+            policy: OverflowPolicy::DropNewest,
+            priority: false,
+        })
+    }
+
+    fn at(ms: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_round_trip() {
+        let path = temp_path("record");
+        let recorder = Recorder::create(&path, 16).unwrap();
+        recorder.record(&rpc_tx(1, 10), at(1_000));
+        recorder.record(&rpc_tx(2, 20), at(1_050));
+        recorder.flush().await;
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let first: serde_json::Value = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+        assert_eq!(first["received_at_ms"], 1_000);
+        assert!(first["raw"].as_str().unwrap().starts_with("0x"));
+
+        let config = ReplayConfig { path: path.clone(), speed: 0.0 };
+        assert_eq!(config.chain_id().await.unwrap(), Some(11155111));
+
+        let (sender, receiver) = queue();
+        let stats = replay(&config, &sender, &SelectorDecoder, &Shutdown::new()).await.unwrap();
+        assert_eq!(stats, ReplayStats { replayed: 2, skipped: 0 });
+
+        let replayed = receiver.recv().await.unwrap();
+        assert_eq!(*replayed.tx.inner.tx_hash(), fixtures::tx_hash(1));
+        assert_eq!(replayed.tx.inner.value(), alloy::primitives::U256::from(10));
+        assert_eq!(*receiver.recv().await.unwrap().tx.inner.tx_hash(), fixtures::tx_hash(2));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replay_keeps_gaps_scaled_by_speed() {
+        let path = temp_path("replay-speed");
+        let lines: Vec<String> = [(0, 1), (400, 2), (1_000, 3)]
+            .iter()
+            .map(|(ms, n)| serde_json::to_string(&RecordedTx::new(rpc_tx(*n, 1), at(*ms))).unwrap())
+            .collect();
+        std::fs::write(&path, lines.join("\n") + "\nnot json\n").unwrap();
+
+        let (sender, _receiver) = queue();
+        let config = ReplayConfig { path: path.clone(), speed: 2.0 };
+        let started = tokio::time::Instant::now();
+        let stats = replay(&config, &sender, &SelectorDecoder, &Shutdown::new()).await.unwrap();
+
+        assert_eq!(stats, ReplayStats { replayed: 3, skipped: 1 });
+        assert_eq!(started.elapsed(), Duration::from_millis(500));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replay_stops_on_shutdown() {
+        let path = temp_path("replay-shutdown");
+        let lines: Vec<String> = [(0, 1), (60_000, 2)]
+            .iter()
+            .map(|(ms, n)| serde_json::to_string(&RecordedTx::new(rpc_tx(*n, 1), at(*ms))).unwrap())
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let (sender, _receiver) = queue();
+        let shutdown = Shutdown::new();
+        let config = ReplayConfig { path: path.clone(), speed: 1.0 };
+
+        let replaying = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { replay(&config, &sender, &SelectorDecoder, &shutdown).await.unwrap() }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        shutdown.trigger();
+
+        assert_eq!(replaying.await.unwrap().replayed, 1);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_replay_waits_for_space_instead_of_dropping() {
+        let path = temp_path("replay-backpressure");
+        let lines: Vec<String> = (1..=5)
+            .map(|n| serde_json::to_string(&RecordedTx::new(rpc_tx(n, 1), at(0))).unwrap())
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let (sender, receiver) = queue_with_capacity(1);
+        let config = ReplayConfig { path: path.clone(), speed: 0.0 };
+        let replaying = tokio::spawn(async move {
+            let stats = replay(&config, &sender, &SelectorDecoder, &Shutdown::new()).await.unwrap();
+            sender.close();
+            stats
+        });
+
+        let mut hashes = Vec::new();
+        while let Some(pending) = receiver.recv().await {
+            hashes.push(*pending.tx.inner.tx_hash());
+        }
+
+        assert_eq!(replaying.await.unwrap().replayed, 5);
+        assert_eq!(hashes, (1..=5).map(fixtures::tx_hash).collect::<Vec<_>>());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_missing_replay_file_is_an_error() {
+        let config = ReplayConfig { path: temp_path("missing"), speed: 1.0 };
+        let (sender, _receiver) = queue();
+        assert!(replay(&config, &sender, &SelectorDecoder, &Shutdown::new()).await.is_err());
+    }
+}
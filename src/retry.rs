@@ -0,0 +1,241 @@
+use crate::latency::Timings;
+use crate::metrics::metrics;
+use alloy::primitives::B256;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+pub const DEFAULT_ATTEMPTS: u32 = 3;
+pub const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(2);
+pub const DEFAULT_MAX_PENDING: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PendingFetch {
+    pub hash: B256,
+    pub timings: Timings,
+    pub attempt: u32,
+}
+
+impl PendingFetch {
+    pub fn new(hash: B256, timings: Timings) -> Self {
+        Self { hash, timings, attempt: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    Scheduled(Duration),
+    Exhausted,
+    Overflow,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub attempts: u32,
+    pub delay: Duration,
+    pub max_delay: Duration,
+    pub max_pending: usize,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_ATTEMPTS,
+            delay: DEFAULT_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            max_pending: DEFAULT_MAX_PENDING,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn from_env() -> Self {
+        Self {
+            attempts: std::env::var("FETCH_RETRY_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_ATTEMPTS),
+            delay: std::env::var("FETCH_RETRY_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_DELAY),
+            max_delay: std::env::var("FETCH_RETRY_MAX_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_MAX_DELAY),
+            max_pending: std::env::var("FETCH_RETRY_MAX_PENDING")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_PENDING),
+        }
+    }
+
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        self.delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+struct State {
+    due: BTreeMap<(Instant, u64), PendingFetch>,
+    seq: u64,
+}
+
+pub struct RetryQueue {
+    config: RetryConfig,
+    state: Mutex<State>,
+    changed: Notify,
+}
+
+impl RetryQueue {
+    pub fn new(config: RetryConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State { due: BTreeMap::new(), seq: 0 }),
+            changed: Notify::new(),
+        }
+    }
+
+    pub fn schedule(&self, fetch: PendingFetch) -> RetryDecision {
+        if fetch.attempt >= self.config.attempts {
+            metrics().fetch_retry_exhausted.inc();
+            return RetryDecision::Exhausted;
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        if state.due.len() >= self.config.max_pending {
+            metrics().fetch_retry_overflow.inc();
+            return RetryDecision::Overflow;
+        }
+
+        let attempt = fetch.attempt + 1;
+        let delay = self.config.delay_for(attempt);
+        let seq = state.seq;
+        state.seq += 1;
+        state.due.insert((Instant::now() + delay, seq), PendingFetch { attempt, ..fetch });
+        metrics().fetch_retry_scheduled.inc();
+        drop(state);
+
+        self.changed.notify_one();
+        RetryDecision::Scheduled(delay)
+    }
+
+    pub fn take_due(&self) -> Vec<PendingFetch> {
+        let mut state = self.state.lock().unwrap();
+        let later = state.due.split_off(&(Instant::now(), u64::MAX));
+        std::mem::replace(&mut state.due, later).into_values().collect()
+    }
+
+    pub async fn wait_due(&self) {
+        loop {
+            let changed = self.changed.notified();
+            let next = self.state.lock().unwrap().due.keys().next().map(|(due, _)| *due);
+
+            match next {
+                Some(due) => tokio::select! {
+                    _ = tokio::time::sleep_until(due) => return,
+                    _ = changed => {}
+                },
+                None => changed.await,
+            }
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.state.lock().unwrap().due.len()
+    }
+}
+
+pub fn record_found(fetch: &PendingFetch) {
+    if fetch.attempt > 0 {
+        metrics().fetch_retry_recovered.inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(attempts: u32, max_pending: usize) -> RetryConfig {
+        RetryConfig {
+            attempts,
+            delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(250),
+            max_pending,
+        }
+    }
+
+    fn fetch(n: u8) -> PendingFetch {
+        PendingFetch::new(B256::repeat_byte(n), Timings::start())
+    }
+
+    #[test]
+    fn test_delay_doubles_up_to_max() {
+        let config = config(5, 10);
+        assert_eq!(config.delay_for(1), Duration::from_millis(100));
+        assert_eq!(config.delay_for(2), Duration::from_millis(200));
+        assert_eq!(config.delay_for(3), Duration::from_millis(250));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_become_due_in_order() {
+        let queue = RetryQueue::new(config(3, 10));
+        assert_eq!(queue.schedule(fetch(1)), RetryDecision::Scheduled(Duration::from_millis(100)));
+
+        let retried = PendingFetch { attempt: 1, ..fetch(2) };
+        assert_eq!(queue.schedule(retried), RetryDecision::Scheduled(Duration::from_millis(200)));
+        assert!(queue.take_due().is_empty());
+
+        queue.wait_due().await;
+        let due = queue.take_due();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].hash, B256::repeat_byte(1));
+        assert_eq!(due[0].attempt, 1);
+
+        queue.wait_due().await;
+        let due = queue.take_due();
+        assert_eq!(due[0].hash, B256::repeat_byte(2));
+        assert_eq!(due[0].attempt, 2);
+        assert_eq!(queue.depth(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_due_wakes_for_earlier_retry() {
+        let queue = std::sync::Arc::new(RetryQueue::new(config(3, 10)));
+        queue.schedule(PendingFetch { attempt: 2, ..fetch(1) });
+
+        let started = Instant::now();
+        let waiter = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.wait_due().await }
+        });
+        tokio::task::yield_now().await;
+        queue.schedule(fetch(2));
+
+        waiter.await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+        assert_eq!(queue.take_due()[0].hash, B256::repeat_byte(2));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_attempts_and_when_full() {
+        let queue = RetryQueue::new(config(2, 1));
+        assert_eq!(queue.schedule(PendingFetch { attempt: 2, ..fetch(1) }), RetryDecision::Exhausted);
+        assert!(matches!(queue.schedule(fetch(2)), RetryDecision::Scheduled(_)));
+        assert_eq!(queue.schedule(fetch(3)), RetryDecision::Overflow);
+    }
+
+    #[tokio::test]
+    async fn test_zero_attempts_disables_retries() {
+        let queue = RetryQueue::new(config(0, 10));
+        assert_eq!(queue.schedule(fetch(1)), RetryDecision::Exhausted);
+        assert_eq!(queue.depth(), 0);
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Semaphore};
+use tokio::time::Instant;
+
+pub const DEFAULT_DRAIN_SECS: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainStage {
+    Queue,
+    Actions,
+    Flush,
+}
+
+impl DrainStage {
+    pub fn budget(self, drain: Duration) -> Duration {
+        match self {
+            DrainStage::Queue => drain / 2,
+            DrainStage::Actions => drain.mul_f64(0.75),
+            DrainStage::Flush => drain,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Shutdown(Arc<watch::Sender<Option<Instant>>>);
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self(Arc::new(watch::channel(None).0))
+    }
+
+    pub fn trigger(&self) {
+        self.0.send_if_modified(|triggered_at| {
+            if triggered_at.is_some() {
+                return false;
+            }
+            *triggered_at = Some(Instant::now());
+            true
+        });
+    }
+
+    pub fn triggered_at(&self) -> Option<Instant> {
+        *self.0.borrow()
+    }
+
+    pub async fn wait(&self) -> Instant {
+        let mut receiver = self.0.subscribe();
+        let triggered_at = match receiver.wait_for(Option::is_some).await {
+            Ok(triggered_at) => *triggered_at,
+            Err(_) => None,
+        };
+
+        match triggered_at {
+            Some(triggered_at) => triggered_at,
+            None => std::future::pending().await,
+        }
+    }
+
+    pub fn deadline(&self, drain: Duration) -> Instant {
+        self.triggered_at().unwrap_or_else(Instant::now) + drain
+    }
+
+    pub async fn expired(&self, drain: Duration) {
+        let triggered_at = self.wait().await;
+        tokio::time::sleep_until(triggered_at + drain).await;
+    }
+}
+
+pub fn drain_timeout_from_env() -> Duration {
+    Duration::from_secs(
+        std::env::var("SHUTDOWN_DRAIN_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_DRAIN_SECS),
+    )
+}
+
+pub async fn drain(semaphore: &Semaphore, permits: usize, deadline: Instant) -> usize {
+    match tokio::time::timeout_at(deadline, semaphore.acquire_many(permits as u32)).await {
+        Ok(Ok(_all)) => 0,
+        _ => permits.saturating_sub(semaphore.available_permits()),
+    }
+}
+
+pub async fn signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "ctrl-c",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                tracing::warn!("failed to install SIGTERM handler: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "ctrl-c"
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "ctrl-c"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_trigger_wakes_waiters_once() {
+        let shutdown = Shutdown::new();
+        assert!(shutdown.triggered_at().is_none());
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        shutdown.trigger();
+        let first = shutdown.triggered_at().unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        shutdown.trigger();
+
+        assert_eq!(waiter.await.unwrap(), first);
+        assert_eq!(shutdown.triggered_at(), Some(first));
+        assert_eq!(shutdown.deadline(Duration::from_secs(5)), first + Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_after_drain_window() {
+        let shutdown = Shutdown::new();
+        shutdown.trigger();
+
+        let started = Instant::now();
+        shutdown.expired(Duration::from_secs(3)).await;
+        assert_eq!(started.elapsed(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_drain_stages_share_one_budget() {
+        let drain = Duration::from_secs(8);
+        assert_eq!(DrainStage::Queue.budget(drain), Duration::from_secs(4));
+        assert_eq!(DrainStage::Actions.budget(drain), Duration::from_secs(6));
+        assert_eq!(DrainStage::Flush.budget(drain), drain);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_waits_for_in_flight_permits() {
+        let semaphore = Arc::new(Semaphore::new(4));
+
+        let quick = semaphore.clone().acquire_owned().await.unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            drop(quick);
+        });
+        assert_eq!(drain(&semaphore, 4, Instant::now() + Duration::from_secs(5)).await, 0);
+
+        let _stuck = semaphore.clone().acquire_many_owned(2).await.unwrap();
+        assert_eq!(drain(&semaphore, 4, Instant::now() + Duration::from_secs(5)).await, 2);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::action::{SniperAction, TargetTransaction};
+use crate::alert_queue::{AlertQueue, DropPolicy, PushOutcome};
+use crate::format::{AlertView, Formatter, Link};
+use crate::metrics::metrics;
+use crate::template::Markup;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+const DEFAULT_MESSAGES_PER_MINUTE: u32 = 60;
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+const MAX_SEND_ATTEMPTS: u32 = 5;
+
+pub const DEFAULT_TEMPLATE: &str = "*value:* {{value}} eth
+*method:* {{method | code}}
+*status:* {{status}}{{#if block}} in block {{block}}{{/if}}
+*from:* {{from}}
+*to:* {{to}}{{#if path}}
+*path:* {{path}}{{/if}}";
+
+#[derive(Debug, Clone)]
+pub struct SlackConfig {
+    pub webhook_url: String,
+    pub messages_per_minute: u32,
+    pub queue_capacity: usize,
+    pub formatter: Formatter,
+}
+
+impl SlackConfig {
+    pub fn from_env(formatter: Formatter) -> Option<Self> {
+        let webhook_url = std::env::var("SLACK_WEBHOOK_URL").ok().filter(|u| !u.is_empty())?;
+
+        Some(Self {
+            webhook_url,
+            messages_per_minute: std::env::var("SLACK_MESSAGES_PER_MINUTE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MESSAGES_PER_MINUTE),
+            queue_capacity: std::env::var("SLACK_QUEUE_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(crate::alert_queue::DEFAULT_CAPACITY),
+            formatter,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SlackSink {
+    client: reqwest::Client,
+    config: Arc<SlackConfig>,
+    queue: Arc<AlertQueue>,
+    started: Arc<AtomicBool>,
+}
+
+impl SlackSink {
+    pub fn new(config: SlackConfig) -> Self {
+        tracing::info!("slack sink initialized ({} msg/min)", config.messages_per_minute);
+
+        Self {
+            client: reqwest::Client::new(),
+            queue: Arc::new(
+                AlertQueue::new(config.queue_capacity, DropPolicy::DropLowestValue, None)
+                    .with_depth(metrics().alert_queue_depth.with_label("slack")),
+            ),
+            config: Arc::new(config),
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    async fn run_sender(self) {
+        let min_interval = Duration::from_secs(60) / self.config.messages_per_minute.max(1);
+        let mut next_send = Instant::now();
+
+        loop {
+            let tx = self.queue.pop().await;
+
+            tokio::time::sleep_until(next_send).await;
+
+            let payload = build_message(&self.config.formatter, &self.config.formatter.view(&tx));
+            if let Err(e) = self.post(&payload).await {
+                metrics().slack_send_failures.inc();
+                tracing::error!("failed to send slack alert for tx {:?}: {}", tx.tx_hash, e);
+            } else {
+                metrics().alerts_delivered.inc();
+            }
+
+            next_send = Instant::now() + min_interval;
+            self.queue.done();
+        }
+    }
+
+    async fn post(&self, payload: &serde_json::Value) -> Result<()> {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            let response = self
+                .client
+                .post(&self.config.webhook_url)
+                .json(payload)
+                .send()
+                .await
+                .context("failed to send slack webhook")?;
+
+            let status = response.status();
+            let retry_after = retry_after_from_headers(response.headers());
+            let body = response.text().await.unwrap_or_default();
+
+            if status.is_success() {
+                return Ok(());
+            }
+
+            if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_SEND_ATTEMPTS {
+                let retry_after = retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+                tracing::warn!(
+                    "slack rate limited - retrying in {:?} (attempt {}/{})",
+                    retry_after,
+                    attempt,
+                    MAX_SEND_ATTEMPTS
+                );
+                tokio::time::sleep(retry_after).await;
+                continue;
+            }
+
+            anyhow::bail!("slack webhook returned error {}: {}", status, body);
+        }
+    }
+}
+
+fn retry_after_from_headers(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get("retry-after")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+struct Mrkdwn;
+
+impl Markup for Mrkdwn {
+    fn escape(&self, text: &str) -> String {
+        escape_mrkdwn(text)
+    }
+
+    fn code(&self, text: &str) -> String {
+        format!("`{}`", escape_mrkdwn(&text.replace('`', "'")))
+    }
+
+    fn link(&self, link: &Link) -> String {
+        match &link.url {
+            Some(url) => format!("<{}|{}>", url, escape_mrkdwn(&link.short)),
+            None => format!("`{}`", link.full),
+        }
+    }
+}
+
+fn button(text: &str, url: &Option<String>) -> Option<serde_json::Value> {
+    url.as_ref().map(|url| {
+        json!({
+            "type": "button",
+            "text": { "type": "plain_text", "text": text },
+            "url": url
+        })
+    })
+}
+
+pub fn build_message(formatter: &Formatter, view: &AlertView) -> serde_json::Value {
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": view.title() }
+        }),
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": formatter.render(view, &Mrkdwn) }
+        }),
+    ];
+
+    let buttons: Vec<_> = [
+        button("view transaction", &view.tx.url),
+        button("from", &view.from.url),
+        button("to", &view.to.url),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if !buttons.is_empty() {
+        blocks.push(json!({ "type": "actions", "elements": buttons }));
+    }
+
+    blocks.push(json!({
+        "type": "context",
+        "elements": [{
+            "type": "mrkdwn",
+            "text": format!(
+                "{} | detected <!date^{}^{{date_short_pretty}} {{time_secs}}|{}>",
+                view.tx.full, view.detected_at_secs, view.detected_at_secs
+            )
+        }]
+    }));
+
+    json!({
+        "text": format!("{}: {} eth ({})", view.title(), view.value_eth, view.method),
+        "blocks": blocks
+    })
+}
+
+#[async_trait]
+impl SniperAction for SlackSink {
+    async fn start(&self) -> Result<()> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        tokio::spawn(self.clone().run_sender());
+
+        Ok(())
+    }
+
+    async fn execute(&self, tx: &TargetTransaction) -> Result<()> {
+        if self.queue.push(tx.clone()).await == PushOutcome::Dropped {
+            tracing::debug!("slack queue full - dropped alert for tx {:?}", tx.tx_hash);
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&self, timeout: Duration) -> Result<()> {
+        if !self.queue.wait_idle(timeout).await {
+            anyhow::bail!("{} slack alerts still queued after {:?}", self.queue.depth(), timeout);
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{self, Explorer};
+    use crate::fixtures;
+    use crate::mock_http::{response, MockHttp};
+    use alloy::primitives::U256;
+
+    fn formatter(explorer: Explorer) -> Formatter {
+        Formatter::new(explorer, crate::template::Template::parse(DEFAULT_TEMPLATE).unwrap())
+    }
+
+    fn config(webhook_url: &str) -> SlackConfig {
+        SlackConfig {
+            webhook_url: webhook_url.to_string(),
+            messages_per_minute: 60_000,
+            queue_capacity: 100,
+            formatter: formatter(Explorer::for_chain(chain::SEPOLIA)),
+        }
+    }
+
+    fn sample_tx() -> TargetTransaction {
+        TargetTransaction {
+            value: U256::from(2_000_000_000_000_000_000u128),
+            ..fixtures::sample_tx()
+        }
+    }
+
+    #[test]
+    fn test_block_kit_layout() {
+        let formatter = formatter(Explorer::for_chain(chain::SEPOLIA));
+        let message = build_message(&formatter, &formatter.view(&sample_tx()));
+        let blocks = message["blocks"].as_array().unwrap();
+        let types: Vec<_> = blocks.iter().map(|b| b["type"].as_str().unwrap()).collect();
+        assert_eq!(types, vec!["header", "section", "actions", "context"]);
+
+        let text = blocks[1]["text"]["text"].as_str().unwrap();
+        assert!(text.starts_with("*value:* 2.0000 eth\n*method:* `swapExactETHForTokens`\n"));
+        assert!(text.contains("*from:* <https://sepolia.etherscan.io/address/0x2222"));
+        assert!(text.contains("*path:* <https://sepolia.etherscan.io/token/0x3333"));
+
+        let buttons = blocks[2]["elements"].as_array().unwrap();
+        assert_eq!(buttons.len(), 3);
+        assert_eq!(
+            buttons[0]["url"],
+            format!("https://sepolia.etherscan.io/tx/0x{}", "11".repeat(32))
+        );
+        assert!(message["text"].as_str().unwrap().contains("2.0000 eth"));
+    }
+
+    #[test]
+    fn test_no_buttons_without_explorer() {
+        let mut tx = sample_tx();
+        tx.path.clear();
+        let formatter = formatter(Explorer::default());
+        let message = build_message(&formatter, &formatter.view(&tx));
+        let types: Vec<_> = message["blocks"].as_array().unwrap().iter().map(|b| b["type"].as_str().unwrap()).collect();
+        assert_eq!(types, vec!["header", "section", "context"]);
+    }
+
+    #[tokio::test]
+    async fn test_retries_after_429() {
+        let server = MockHttp::start(vec![
+            response("429 Too Many Requests", "Retry-After: 0\r\n", ""),
+            response("200 OK", "", "ok"),
+        ])
+        .await;
+
+        let sink = SlackSink::new(config(&server.url));
+        sink.start().await.unwrap();
+        sink.execute(&sample_tx()).await.unwrap();
+        sink.flush(Duration::from_secs(5)).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].json()["blocks"][0]["type"], "header");
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_error_fails() {
+        let server = MockHttp::start(vec![response("400 Bad Request", "", "invalid_blocks")]).await;
+
+        let sink = SlackSink::new(config(&server.url));
+        let result = sink.post(&json!({ "text": "hello" })).await;
+
+        assert!(result.unwrap_err().to_string().contains("invalid_blocks"));
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sender_runs_only_after_start() {
+        let server = MockHttp::start(vec![response("200 OK", "", "ok")]).await;
+
+        let sink = SlackSink::new(config(&server.url));
+        sink.execute(&sample_tx()).await.unwrap();
+        assert!(!sink.queue.wait_idle(Duration::from_millis(50)).await);
+        assert_eq!(server.hits(), 0);
+
+        sink.start().await.unwrap();
+        sink.start().await.unwrap();
+        sink.flush(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(server.hits(), 1);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::metrics::metrics;
+use crate::shutdown::Shutdown;
+use futures_util::FutureExt;
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum StageExit {
+    Stopped,
+    Finished,
+    Failed(anyhow::Error),
+}
+
+pub fn supervise<F, Fut>(pipeline: &str, stage: &'static str, shutdown: Shutdown, mut start: F) -> JoinHandle<anyhow::Result<()>>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = StageExit> + Send + 'static,
+{
+    let pipeline = pipeline.to_string();
+
+    tokio::spawn(async move {
+        let mut backoff = INITIAL_BACKOFF;
+
+        let result = loop {
+            metrics().set_stage_up(&pipeline, stage, true);
+            let started_at = Instant::now();
+
+            let error = match tokio::spawn(start()).await {
+                Ok(StageExit::Failed(e)) => {
+                    tracing::error!("{} failed: {:#}", stage, e);
+                    break Err(e);
+                }
+                Ok(_) if shutdown.triggered_at().is_some() => break Ok(()),
+                Ok(StageExit::Finished) => {
+                    tracing::info!("{} finished", stage);
+                    break Ok(());
+                }
+                Ok(StageExit::Stopped) => "exited unexpectedly".to_string(),
+                Err(e) if e.is_panic() => format!("panicked: {}", panic_message(&*e.into_panic())),
+                Err(e) => format!("was cancelled: {}", e),
+            };
+
+            if shutdown.triggered_at().is_some() {
+                tracing::warn!("{} {} during shutdown", stage, error);
+                break Ok(());
+            }
+
+            if started_at.elapsed() >= STABLE_AFTER {
+                backoff = INITIAL_BACKOFF;
+            }
+
+            metrics().set_stage_up(&pipeline, stage, false);
+            metrics().record_restart(&pipeline, stage, &error);
+            tracing::error!("{} {} - restarting in {:?}", stage, error, backoff);
+
+            tokio::select! {
+                _ = shutdown.wait() => break Ok(()),
+                _ = tokio::time::sleep(backoff) => {}
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        };
+
+        metrics().set_stage_up(&pipeline, stage, false);
+        tracing::info!("{} stopped", stage);
+        result
+    })
+}
+
+pub fn spawn_task<F>(stage: &'static str, task: F) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(panic) = AssertUnwindSafe(task).catch_unwind().await {
+            metrics().record_panic(stage);
+            tracing::error!("{} task panicked: {}", stage, panic_message(&*panic));
+        }
+    })
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_restarts_failed_stage_with_backoff() {
+        let shutdown = Shutdown::new();
+        let runs = Arc::new(AtomicU32::new(0));
+        let started = Instant::now();
+
+        let handle = supervise("test", "test_flaky", shutdown.clone(), {
+            let runs = runs.clone();
+            let shutdown = shutdown.clone();
+            move || {
+                let run = runs.fetch_add(1, Ordering::SeqCst);
+                let shutdown = shutdown.clone();
+                async move {
+                    match run {
+                        0 => panic!("boom"),
+                        1 => StageExit::Stopped,
+                        _ => {
+                            shutdown.wait().await;
+                            StageExit::Stopped
+                        }
+                    }
+                }
+            }
+        });
+
+        while runs.load(Ordering::SeqCst) < 3 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(started.elapsed() >= INITIAL_BACKOFF * 3, "second restart waits twice as long");
+
+        let health = metrics().stage_health("test", "test_flaky").unwrap();
+        assert!(health.up);
+        assert_eq!(health.restarts, 2);
+        assert_eq!(health.last_error.as_deref(), Some("exited unexpectedly"));
+
+        shutdown.trigger();
+        handle.await.unwrap().unwrap();
+        assert!(!metrics().stage_health("test", "test_flaky").unwrap().up);
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_spawned_task_panic_is_recorded() {
+        spawn_task("test_task", async { panic!("bad tx {}", 7) }).await.unwrap();
+        spawn_task("test_task", async {}).await.unwrap();
+
+        assert_eq!(metrics().task_panics("test_task"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_finished_stage_is_not_restarted() {
+        let runs = Arc::new(AtomicU32::new(0));
+
+        supervise("test", "test_finite", Shutdown::new(), {
+            let runs = runs.clone();
+            move || {
+                runs.fetch_add(1, Ordering::SeqCst);
+                async { StageExit::Finished }
+            }
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        let health = metrics().stage_health("test", "test_finite").unwrap();
+        assert!(!health.up);
+        assert_eq!(health.restarts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_stage_returns_its_error() {
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let result = supervise("test", "test_failing", Shutdown::new(), {
+            let runs = runs.clone();
+            move || {
+                runs.fetch_add(1, Ordering::SeqCst);
+                async { StageExit::Failed(anyhow::anyhow!("bad input")) }
+            }
+        })
+        .await
+        .unwrap();
+
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "bad input");
+        assert_eq!(runs.load(Ordering::SeqCst), 1, "failed stages are not restarted");
+        assert!(!metrics().stage_health("test", "test_failing").unwrap().up);
+    }
+}
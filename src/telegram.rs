@@ -0,0 +1,405 @@
+use crate::action::{SniperAction, TargetTransaction};
+use crate::alert_queue::{AlertQueue, DropPolicy, PushOutcome};
+use crate::format::{AlertView, Formatter, Link};
+use crate::metrics::metrics;
+use crate::template::Markup;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use reqwest::StatusCode;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+const DEFAULT_MESSAGES_PER_MINUTE: u32 = 20;
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+const MAX_SEND_ATTEMPTS: u32 = 5;
+
+pub const DEFAULT_TEMPLATE: &str = "*transaction {{status}}*
+value: *{{value}} eth*
+method: {{method | code}}
+tx: {{tx}}{{#if block}}
+block: {{block}}{{/if}}
+from: {{from}}
+to: {{to}}
+{{#if path}}path: {{path}}
+{{/if}}detected: {{detected_time}}";
+
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub api_url: String,
+    pub bot_token: String,
+    pub chat_ids: Vec<String>,
+    pub messages_per_minute: u32,
+    pub queue_capacity: usize,
+    pub formatter: Formatter,
+}
+
+impl TelegramConfig {
+    pub fn from_env(formatter: Formatter) -> Option<Self> {
+        let bot_token = std::env::var("TELEGRAM_BOT_TOKEN").ok().filter(|t| !t.is_empty())?;
+        let chat_ids: Vec<String> = std::env::var("TELEGRAM_CHAT_IDS")
+            .ok()?
+            .split(',')
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+
+        if chat_ids.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            api_url: std::env::var("TELEGRAM_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string()),
+            bot_token,
+            chat_ids,
+            messages_per_minute: std::env::var("TELEGRAM_MESSAGES_PER_MINUTE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MESSAGES_PER_MINUTE),
+            queue_capacity: std::env::var("TELEGRAM_QUEUE_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(crate::alert_queue::DEFAULT_CAPACITY),
+            formatter,
+        })
+    }
+}
+
+struct Chat {
+    id: String,
+    queue: AlertQueue,
+}
+
+#[derive(Clone)]
+pub struct TelegramSink {
+    client: reqwest::Client,
+    config: Arc<TelegramConfig>,
+    chats: Vec<Arc<Chat>>,
+    started: Arc<AtomicBool>,
+}
+
+impl TelegramSink {
+    pub fn new(config: TelegramConfig) -> Self {
+        tracing::info!(
+            "telegram sink initialized ({} chats, {} msg/min per chat)",
+            config.chat_ids.len(),
+            config.messages_per_minute
+        );
+
+        let chats = config
+            .chat_ids
+            .iter()
+            .map(|id| {
+                Arc::new(Chat {
+                    id: id.clone(),
+                    queue: AlertQueue::new(config.queue_capacity, DropPolicy::DropLowestValue, None)
+                        .with_depth(metrics().alert_queue_depth.with_label(&format!("telegram:{}", id))),
+                })
+            })
+            .collect();
+
+        Self {
+            client: reqwest::Client::new(),
+            config: Arc::new(config),
+            chats,
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    async fn run_chat(self, chat: Arc<Chat>) {
+        let min_interval = Duration::from_secs(60) / self.config.messages_per_minute.max(1);
+        let mut next_send = Instant::now();
+
+        loop {
+            let tx = chat.queue.pop().await;
+
+            tokio::time::sleep_until(next_send).await;
+
+            let text = render_message(&self.config.formatter, &self.config.formatter.view(&tx));
+            if let Err(e) = self.send_message(&chat.id, &text).await {
+                metrics().telegram_send_failures.inc();
+                tracing::error!("failed to send telegram alert to chat {}: {}", chat.id, e);
+            } else {
+                metrics().alerts_delivered.inc();
+            }
+
+            next_send = Instant::now() + min_interval;
+            chat.queue.done();
+        }
+    }
+
+    async fn send_message(&self, chat_id: &str, text: &str) -> Result<()> {
+        let url = format!("{}/bot{}/sendMessage", self.config.api_url, self.config.bot_token);
+        let payload = json!({
+            "chat_id": chat_id,
+            "text": text,
+            "parse_mode": "MarkdownV2",
+            "disable_web_page_preview": true
+        });
+
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            let response = self
+                .client
+                .post(&url)
+                .json(&payload)
+                .send()
+                .await
+                .context("failed to send telegram message")?;
+
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+
+            if status.is_success() {
+                return Ok(());
+            }
+
+            if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_SEND_ATTEMPTS {
+                let retry_after = retry_after_from_body(&body).unwrap_or(DEFAULT_RETRY_AFTER);
+                tracing::warn!(
+                    "telegram rate limited chat {} - retrying in {:?} (attempt {}/{})",
+                    chat_id,
+                    retry_after,
+                    attempt,
+                    MAX_SEND_ATTEMPTS
+                );
+                tokio::time::sleep(retry_after).await;
+                continue;
+            }
+
+            anyhow::bail!("telegram api returned error {}: {}", status, body);
+        }
+    }
+}
+
+fn retry_after_from_body(body: &str) -> Option<Duration> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let secs = value.get("parameters")?.get("retry_after")?.as_u64()?;
+    Some(Duration::from_secs(secs))
+}
+
+const MARKDOWN_SPECIAL: &[char] = &[
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!', '\\',
+];
+
+pub fn escape_markdown(text: &str) -> String {
+    escape_chars(text, MARKDOWN_SPECIAL)
+}
+
+fn escape_chars(text: &str, special: &[char]) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+struct MarkdownV2;
+
+impl Markup for MarkdownV2 {
+    fn escape(&self, text: &str) -> String {
+        escape_markdown(text)
+    }
+
+    fn code(&self, text: &str) -> String {
+        format!("`{}`", escape_chars(text, &['`', '\\']))
+    }
+
+    fn link(&self, link: &Link) -> String {
+        match &link.url {
+            Some(url) => format!("[{}]({})", escape_markdown(&link.short), escape_chars(url, &[')', '\\'])),
+            None => self.code(&link.full),
+        }
+    }
+}
+
+pub fn render_message(formatter: &Formatter, view: &AlertView) -> String {
+    formatter.render(view, &MarkdownV2)
+}
+
+#[async_trait]
+impl SniperAction for TelegramSink {
+    async fn start(&self) -> Result<()> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        for chat in &self.chats {
+            tokio::spawn(self.clone().run_chat(chat.clone()));
+        }
+
+        Ok(())
+    }
+
+    async fn execute(&self, tx: &TargetTransaction) -> Result<()> {
+        for chat in &self.chats {
+            if chat.queue.push(tx.clone()).await == PushOutcome::Dropped {
+                tracing::debug!("telegram queue for chat {} full - dropped alert for tx {:?}", chat.id, tx.tx_hash);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&self, timeout: Duration) -> Result<()> {
+        let idle = join_all(self.chats.iter().map(|chat| chat.queue.wait_idle(timeout))).await;
+        let pending: usize = self
+            .chats
+            .iter()
+            .zip(idle)
+            .filter(|(_, idle)| !idle)
+            .map(|(chat, _)| chat.queue.depth())
+            .sum();
+
+        if pending > 0 {
+            anyhow::bail!("{} telegram alerts still queued after {:?}", pending, timeout);
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{self, Explorer};
+    use crate::fixtures;
+    use crate::mock_http::{response, MockHttp};
+    use alloy::primitives::{Address, U256};
+
+    fn template() -> crate::template::Template {
+        crate::template::Template::parse(DEFAULT_TEMPLATE).unwrap()
+    }
+
+    fn config(api_url: &str, chat_ids: &[&str]) -> TelegramConfig {
+        TelegramConfig {
+            api_url: api_url.to_string(),
+            bot_token: "123:abc".to_string(),
+            chat_ids: chat_ids.iter().map(|id| id.to_string()).collect(),
+            messages_per_minute: 60_000,
+            queue_capacity: 100,
+            formatter: Formatter::new(Explorer::for_chain(chain::SEPOLIA), template()),
+        }
+    }
+
+    fn sample_tx() -> TargetTransaction {
+        TargetTransaction {
+            value: U256::from(1_500_000_000_000_000_000u128),
+            method: "swap_exact.eth".to_string(),
+            path: vec![Address::from([0x33; 20]), Address::from([0x44; 20])],
+            to: Address::ZERO,
+            ..fixtures::sample_tx()
+        }
+    }
+
+    #[test]
+    fn test_escape_markdown() {
+        assert_eq!(escape_markdown("1.5 eth (swap)!"), "1\\.5 eth \\(swap\\)\\!");
+        assert_eq!(escape_markdown("a_b*c"), "a\\_b\\*c");
+    }
+
+    #[test]
+    fn test_render_message() {
+        let formatter = Formatter::new(Explorer::for_chain(chain::SEPOLIA), template());
+        let text = render_message(&formatter, &formatter.view(&sample_tx()));
+
+        assert!(text.contains("value: *1\\.5000 eth*"));
+        assert!(text.contains("method: `swap_exact.eth`"));
+        assert!(text.contains(&format!(
+            "tx: [0x11111111\\.\\.\\.11111111](https://sepolia.etherscan.io/tx/0x{})",
+            "11".repeat(32)
+        )));
+        assert!(text.contains("path: [0x3333\\.\\.\\.3333]"));
+        assert!(text.contains("detected: 2023\\-11\\-14 22:13:20 UTC"));
+    }
+
+    #[tokio::test]
+    async fn test_sends_to_every_chat() {
+        let server = MockHttp::start(vec![
+            response("200 OK", "", r#"{"ok": true, "result": {}}"#),
+            response("200 OK", "", r#"{"ok": true, "result": {}}"#),
+        ])
+        .await;
+
+        let sink = TelegramSink::new(config(&server.url, &["100", "-200"]));
+        sink.start().await.unwrap();
+        sink.execute(&sample_tx()).await.unwrap();
+        sink.flush(Duration::from_secs(5)).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r.path == "/bot123:abc/sendMessage"));
+
+        let mut chats: Vec<_> = requests.iter().map(|r| r.json()["chat_id"].as_str().unwrap().to_string()).collect();
+        chats.sort();
+        assert_eq!(chats, vec!["-200", "100"]);
+        assert_eq!(requests[0].json()["parse_mode"], "MarkdownV2");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flush_shares_one_timeout_across_chats() {
+        let sink = TelegramSink::new(config("http://127.0.0.1:9/unused", &["100", "200", "300"]));
+        sink.execute(&sample_tx()).await.unwrap();
+
+        let started = Instant::now();
+        let err = sink.flush(Duration::from_millis(100)).await.unwrap_err();
+
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+        assert!(err.to_string().starts_with("3 telegram alerts"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_retries_after_429() {
+        let server = MockHttp::start(vec![
+            response(
+                "429 Too Many Requests",
+                "",
+                r#"{"ok": false, "error_code": 429, "description": "Too Many Requests: retry after 0", "parameters": {"retry_after": 0}}"#,
+            ),
+            response("200 OK", "", r#"{"ok": true, "result": {}}"#),
+        ])
+        .await;
+
+        let sink = TelegramSink::new(config(&server.url, &["100"]));
+        let result = sink.send_message("100", "hello").await;
+
+        assert!(result.is_ok(), "message should be delivered after retry: {:?}", result);
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_per_chat_rate_limit() {
+        let server = MockHttp::start(vec![
+            response("200 OK", "", r#"{"ok": true, "result": {}}"#),
+            response("200 OK", "", r#"{"ok": true, "result": {}}"#),
+        ])
+        .await;
+
+        let sink = TelegramSink::new(TelegramConfig {
+            messages_per_minute: 300,
+            ..config(&server.url, &["100"])
+        });
+        sink.start().await.unwrap();
+
+        let started = Instant::now();
+        sink.execute(&sample_tx()).await.unwrap();
+        sink.execute(&sample_tx()).await.unwrap();
+        sink.flush(Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(server.hits(), 2);
+        assert!(started.elapsed() >= Duration::from_millis(200), "second message should wait for the chat interval");
+    }
+}
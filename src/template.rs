@@ -0,0 +1,369 @@
+use crate::format::Link;
+use anyhow::{Context as _, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+const SELECTORS: &[&str] = &["code", "short", "full", "url", "len"];
+const MODIFIERS: &[&str] = &["upper", "lower", "red", "green", "yellow", "blue", "magenta", "cyan", "bold"];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("unclosed tag starting at byte {0}")]
+    UnclosedTag(usize),
+    #[error("empty tag at byte {0}")]
+    EmptyTag(usize),
+    #[error("unknown filter '{0}'")]
+    UnknownFilter(String),
+    #[error("filter '{0}' must come first")]
+    MisplacedFilter(String),
+    #[error("unexpected {{{{{0}}}}}")]
+    Unexpected(String),
+    #[error("unclosed {{{{#if {0}}}}}")]
+    UnclosedIf(String),
+    #[error("unknown variable '{0}'")]
+    UnknownVariable(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Text(String),
+    Link(Link),
+    List(Vec<Link>),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Text(s) => !s.is_empty(),
+            Value::Link(_) => true,
+            Value::List(links) => !links.is_empty(),
+        }
+    }
+
+    fn full(&self) -> String {
+        match self {
+            Value::Text(s) => s.clone(),
+            Value::Link(link) => link.full.clone(),
+            Value::List(links) => links.iter().map(|l| l.full.as_str()).collect::<Vec<_>>().join(", "),
+        }
+    }
+
+    fn short(&self) -> String {
+        match self {
+            Value::Text(s) => s.clone(),
+            Value::Link(link) => link.short.clone(),
+            Value::List(links) => links.iter().map(|l| l.short.as_str()).collect::<Vec<_>>().join(" → "),
+        }
+    }
+}
+
+pub type Context = HashMap<&'static str, Value>;
+
+pub trait Markup {
+    fn escape(&self, text: &str) -> String;
+
+    fn code(&self, text: &str) -> String;
+
+    fn link(&self, link: &Link) -> String;
+
+    fn color(&self, _color: &str, text: String) -> String {
+        text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var { name: String, filters: Vec<String> },
+    If { name: String, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut tokens = tokenize(source)?.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens)?;
+
+        match end {
+            None => Ok(Self { nodes }),
+            Some(tag) => Err(TemplateError::Unexpected(tag)),
+        }
+    }
+
+    pub fn check(&self, known: &[&str]) -> Result<(), TemplateError> {
+        fn walk(nodes: &[Node], known: &[&str]) -> Result<(), TemplateError> {
+            for node in nodes {
+                match node {
+                    Node::Text(_) => {}
+                    Node::Var { name, .. } if !known.contains(&name.as_str()) => {
+                        return Err(TemplateError::UnknownVariable(name.clone()));
+                    }
+                    Node::Var { .. } => {}
+                    Node::If { name, then, otherwise } => {
+                        if !known.contains(&name.as_str()) {
+                            return Err(TemplateError::UnknownVariable(name.clone()));
+                        }
+                        walk(then, known)?;
+                        walk(otherwise, known)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        walk(&self.nodes, known)
+    }
+
+    pub fn render(&self, context: &Context, markup: &dyn Markup) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, context, markup, &mut out);
+        out
+    }
+}
+
+pub fn load(name: &str, default: &str, known: &[&str]) -> Result<Template> {
+    let source = match std::env::var("TEMPLATE_DIR") {
+        Ok(dir) => {
+            let path = PathBuf::from(dir).join(format!("{}.tmpl", name));
+            if path.exists() {
+                tracing::info!("loading {} template from {}", name, path.display());
+                std::fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?
+            } else {
+                default.to_string()
+            }
+        }
+        Err(_) => default.to_string(),
+    };
+
+    let template = Template::parse(&source).with_context(|| format!("invalid {} template", name))?;
+    template.check(known).with_context(|| format!("invalid {} template", name))?;
+    Ok(template)
+}
+
+enum Token {
+    Text(String),
+    Tag(String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut offset = 0;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or(TemplateError::UnclosedTag(offset + start))?;
+        let tag = after[..end].trim();
+        if tag.is_empty() {
+            return Err(TemplateError::EmptyTag(offset + start));
+        }
+        tokens.push(Token::Tag(tag.to_string()));
+
+        let consumed = start + 2 + end + 2;
+        offset += consumed;
+        rest = &rest[consumed..];
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_nodes(tokens: &mut impl Iterator<Item = Token>) -> Result<(Vec<Node>, Option<String>), TemplateError> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Token::Tag(tag) => tag,
+        };
+
+        if let Some(name) = tag.strip_prefix("#if") {
+            let name = name.trim().to_string();
+            let (then, end) = parse_nodes(tokens)?;
+            let otherwise = match end.as_deref() {
+                Some("/if") => Vec::new(),
+                Some("else") => match parse_nodes(tokens)? {
+                    (otherwise, Some(end)) if end == "/if" => otherwise,
+                    (_, Some(end)) => return Err(TemplateError::Unexpected(end)),
+                    (_, None) => return Err(TemplateError::UnclosedIf(name)),
+                },
+                Some(other) => return Err(TemplateError::Unexpected(other.to_string())),
+                None => return Err(TemplateError::UnclosedIf(name)),
+            };
+            nodes.push(Node::If { name, then, otherwise });
+        } else if tag == "else" || tag == "/if" {
+            return Ok((nodes, Some(tag)));
+        } else {
+            let mut parts = tag.split('|').map(|p| p.trim().to_string());
+            let name = parts.next().unwrap_or_default();
+            let filters: Vec<String> = parts.collect();
+
+            for (i, filter) in filters.iter().enumerate() {
+                if SELECTORS.contains(&filter.as_str()) {
+                    if i > 0 {
+                        return Err(TemplateError::MisplacedFilter(filter.clone()));
+                    }
+                } else if !MODIFIERS.contains(&filter.as_str()) {
+                    return Err(TemplateError::UnknownFilter(filter.clone()));
+                }
+            }
+
+            nodes.push(Node::Var { name, filters });
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn render_nodes(nodes: &[Node], context: &Context, markup: &dyn Markup, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { name, filters } => {
+                if let Some(value) = context.get(name.as_str()) {
+                    out.push_str(&render_value(value, filters, markup));
+                }
+            }
+            Node::If { name, then, otherwise } => {
+                let truthy = context.get(name.as_str()).is_some_and(Value::truthy);
+                render_nodes(if truthy { then } else { otherwise }, context, markup, out);
+            }
+        }
+    }
+}
+
+fn render_value(value: &Value, filters: &[String], markup: &dyn Markup) -> String {
+    let (selector, modifiers) = match filters.split_first() {
+        Some((first, rest)) if SELECTORS.contains(&first.as_str()) => (Some(first.as_str()), rest),
+        _ => (None, filters),
+    };
+
+    let mut out = match selector {
+        Some("code") => markup.code(&value.full()),
+        Some("short") => markup.escape(&value.short()),
+        Some("full") => markup.escape(&value.full()),
+        Some("url") => match value {
+            Value::Link(Link { url: Some(url), .. }) => markup.escape(url),
+            _ => String::new(),
+        },
+        Some("len") => match value {
+            Value::List(links) => links.len().to_string(),
+            Value::Text(text) => text.chars().count().to_string(),
+            Value::Link(_) => "1".to_string(),
+        },
+        _ => match value {
+            Value::Text(text) => markup.escape(text),
+            Value::Link(link) => markup.link(link),
+            Value::List(links) => links.iter().map(|l| markup.link(l)).collect::<Vec<_>>().join(" → "),
+        },
+    };
+
+    let (case, colors): (Vec<_>, Vec<_>) = modifiers.iter().partition(|m| matches!(m.as_str(), "upper" | "lower"));
+
+    for modifier in case {
+        out = match modifier.as_str() {
+            "upper" => out.to_uppercase(),
+            _ => out.to_lowercase(),
+        };
+    }
+
+    for color in colors {
+        out = markup.color(color, out);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Brackets;
+
+    impl Markup for Brackets {
+        fn escape(&self, text: &str) -> String {
+            text.replace('*', "\\*")
+        }
+
+        fn code(&self, text: &str) -> String {
+            format!("`{}`", text)
+        }
+
+        fn link(&self, link: &Link) -> String {
+            format!("[{}]({})", link.short, link.url.as_deref().unwrap_or("-"))
+        }
+
+        fn color(&self, color: &str, text: String) -> String {
+            format!("<{}>{}</{}>", color, text, color)
+        }
+    }
+
+    fn context() -> Context {
+        let link = |short: &str| Link {
+            short: short.to_string(),
+            full: format!("{}-full", short),
+            url: Some(format!("https://x/{}", short)),
+        };
+
+        let mut ctx = Context::new();
+        ctx.insert("value", Value::Text("1*5".to_string()));
+        ctx.insert("empty", Value::Text(String::new()));
+        ctx.insert("tx", Value::Link(link("0xab")));
+        ctx.insert("path", Value::List(vec![link("a"), link("b")]));
+        ctx
+    }
+
+    #[test]
+    fn test_variables_and_filters() {
+        let template = Template::parse("v={{ value }} tx={{tx}} {{tx | full | upper}} {{tx|url}} {{path}} n={{path | len | green}} {{value|code}}").unwrap();
+        assert_eq!(
+            template.render(&context(), &Brackets),
+            "v=1\\*5 tx=[0xab](https://x/0xab) 0XAB-FULL https://x/0xab [a](https://x/a) → [b](https://x/b) n=<green>2</green> `1*5`"
+        );
+    }
+
+    #[test]
+    fn test_colors_applied_after_case_modifiers() {
+        let template = Template::parse("{{tx | full | red | upper}} {{value | bold | lower | green}}").unwrap();
+        assert_eq!(template.render(&context(), &Brackets), "<red>0XAB-FULL</red> <green><bold>1\\*5</bold></green>");
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let template = Template::parse("{{#if path}}path{{/if}}|{{#if empty}}yes{{else}}no{{/if}}|{{#if missing}}x{{/if}}").unwrap();
+        assert_eq!(template.render(&context(), &Brackets), "path|no|");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Template::parse("{{value"), Err(TemplateError::UnclosedTag(0)));
+        assert_eq!(Template::parse("a {{ }}"), Err(TemplateError::EmptyTag(2)));
+        assert_eq!(Template::parse("{{value | shout}}"), Err(TemplateError::UnknownFilter("shout".to_string())));
+        assert_eq!(Template::parse("{{value | upper | code}}"), Err(TemplateError::MisplacedFilter("code".to_string())));
+        assert_eq!(Template::parse("{{#if value}}x"), Err(TemplateError::UnclosedIf("value".to_string())));
+        assert_eq!(Template::parse("x{{/if}}"), Err(TemplateError::Unexpected("/if".to_string())));
+    }
+
+    #[test]
+    fn test_check_unknown_variables() {
+        let template = Template::parse("{{#if path}}{{valeu}}{{/if}}").unwrap();
+        assert_eq!(template.check(&["path", "value"]), Err(TemplateError::UnknownVariable("valeu".to_string())));
+        assert!(template.check(&["path", "valeu"]).is_ok());
+    }
+}
@@ -0,0 +1,412 @@
+use crate::metrics::metrics;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+pub const DEFAULT_MIN_CONCURRENCY: usize = 4;
+pub const DEFAULT_MAX_CONCURRENCY: usize = 200;
+pub const DEFAULT_INITIAL_CONCURRENCY: usize = 50;
+pub const DEFAULT_LATENCY_TARGET: Duration = Duration::from_millis(500);
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+pub const DEFAULT_CU_PER_FETCH: u32 = 17;
+
+const DECREASE_COOLDOWN: Duration = Duration::from_secs(1);
+const MAX_ERROR_RATIO: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchOutcome {
+    Ok(Duration),
+    RateLimited,
+    Timeout,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct AimdConfig {
+    pub min: usize,
+    pub max: usize,
+    pub initial: usize,
+    pub latency_target: Duration,
+    pub fetch_timeout: Duration,
+}
+
+impl Default for AimdConfig {
+    fn default() -> Self {
+        Self {
+            min: DEFAULT_MIN_CONCURRENCY,
+            max: DEFAULT_MAX_CONCURRENCY,
+            initial: DEFAULT_INITIAL_CONCURRENCY,
+            latency_target: DEFAULT_LATENCY_TARGET,
+            fetch_timeout: DEFAULT_FETCH_TIMEOUT,
+        }
+    }
+}
+
+impl AimdConfig {
+    pub fn from_env() -> Self {
+        let env = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        let min = env("FETCH_CONCURRENCY_MIN", DEFAULT_MIN_CONCURRENCY as u64).max(1) as usize;
+        let max = (env("FETCH_CONCURRENCY_MAX", DEFAULT_MAX_CONCURRENCY as u64) as usize).max(min);
+
+        Self {
+            min,
+            max,
+            initial: (env("FETCH_CONCURRENCY_INITIAL", DEFAULT_INITIAL_CONCURRENCY as u64) as usize).clamp(min, max),
+            latency_target: Duration::from_millis(env(
+                "FETCH_LATENCY_TARGET_MS",
+                DEFAULT_LATENCY_TARGET.as_millis() as u64,
+            )),
+            fetch_timeout: Duration::from_millis(env("FETCH_TIMEOUT_MS", DEFAULT_FETCH_TIMEOUT.as_millis() as u64)),
+        }
+    }
+}
+
+struct Window {
+    limit: usize,
+    samples: usize,
+    healthy: usize,
+    errors: usize,
+    last_decrease: Option<Instant>,
+}
+
+pub struct AdaptiveLimiter {
+    config: AimdConfig,
+    semaphore: Arc<Semaphore>,
+    window: Mutex<Window>,
+    debt: AtomicUsize,
+    frozen: AtomicBool,
+}
+
+pub struct FetchPermit {
+    permit: Option<OwnedSemaphorePermit>,
+    limiter: Arc<AdaptiveLimiter>,
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+
+        let repaid = self
+            .limiter
+            .debt
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |debt| debt.checked_sub(1))
+            .is_ok();
+        if repaid {
+            permit.forget();
+        }
+        self.limiter.publish();
+    }
+}
+
+impl AdaptiveLimiter {
+    pub fn new(config: AimdConfig) -> Arc<Self> {
+        let limiter = Arc::new(Self {
+            semaphore: Arc::new(Semaphore::new(config.initial)),
+            window: Mutex::new(Window {
+                limit: config.initial,
+                samples: 0,
+                healthy: 0,
+                errors: 0,
+                last_decrease: None,
+            }),
+            debt: AtomicUsize::new(0),
+            frozen: AtomicBool::new(false),
+            config,
+        });
+        limiter.publish();
+        limiter
+    }
+
+    pub fn fetch_timeout(&self) -> Duration {
+        self.config.fetch_timeout
+    }
+
+    pub fn limit(&self) -> usize {
+        self.window.lock().unwrap().limit
+    }
+
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    pub async fn acquire(self: &Arc<Self>) -> FetchPermit {
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("fetch semaphore is never closed");
+        let permit = FetchPermit {
+            permit: Some(permit),
+            limiter: self.clone(),
+        };
+        self.publish();
+        permit
+    }
+
+    pub fn try_acquire(self: &Arc<Self>) -> Option<FetchPermit> {
+        let permit = self.semaphore.clone().try_acquire_owned().ok()?;
+        let permit = FetchPermit {
+            permit: Some(permit),
+            limiter: self.clone(),
+        };
+        self.publish();
+        Some(permit)
+    }
+
+    pub fn record(&self, outcome: FetchOutcome) {
+        if self.frozen.load(Ordering::Acquire) {
+            return;
+        }
+
+        let mut window = self.window.lock().unwrap();
+        let old = window.limit;
+
+        match outcome {
+            FetchOutcome::RateLimited | FetchOutcome::Timeout => {
+                let cooling = window.last_decrease.is_some_and(|at| at.elapsed() < DECREASE_COOLDOWN);
+                if !cooling {
+                    self.resize(&mut window, old / 2);
+                    window.last_decrease = Some(Instant::now());
+                    tracing::info!(
+                        "fetch concurrency {} -> {} ({})",
+                        old,
+                        window.limit,
+                        if outcome == FetchOutcome::Timeout { "timeout" } else { "rate limited" }
+                    );
+                }
+                return;
+            }
+            FetchOutcome::Ok(latency) => {
+                window.samples += 1;
+                if latency <= self.config.latency_target {
+                    window.healthy += 1;
+                }
+            }
+            FetchOutcome::Error => {
+                window.samples += 1;
+                window.errors += 1;
+            }
+        }
+
+        if window.samples < window.limit {
+            return;
+        }
+
+        let error_ratio = window.errors as f64 / window.samples as f64;
+        if error_ratio > MAX_ERROR_RATIO {
+            self.resize(&mut window, old * 3 / 4);
+            window.last_decrease = Some(Instant::now());
+            tracing::info!("fetch concurrency {} -> {} ({:.0}% errors)", old, window.limit, error_ratio * 100.0);
+        } else if window.healthy == window.samples {
+            self.resize(&mut window, old + 1);
+            tracing::debug!("fetch concurrency {} -> {}", old, window.limit);
+        }
+
+        window.samples = 0;
+        window.healthy = 0;
+        window.errors = 0;
+    }
+
+    pub async fn drain(&self, deadline: Instant) -> usize {
+        self.frozen.store(true, Ordering::Release);
+        crate::shutdown::drain(&self.semaphore, self.limit(), deadline).await
+    }
+
+    fn resize(&self, window: &mut Window, target: usize) {
+        let target = target.clamp(self.config.min, self.config.max);
+
+        if target > window.limit {
+            let mut grow = target - window.limit;
+            while grow > 0 {
+                let cancelled = self.debt.fetch_update(Ordering::AcqRel, Ordering::Acquire, |debt| debt.checked_sub(1));
+                if cancelled.is_err() {
+                    break;
+                }
+                grow -= 1;
+            }
+            self.semaphore.add_permits(grow);
+        } else {
+            for _ in target..window.limit {
+                match self.semaphore.try_acquire() {
+                    Ok(permit) => permit.forget(),
+                    Err(_) => {
+                        self.debt.fetch_add(1, Ordering::AcqRel);
+                    }
+                }
+            }
+        }
+
+        window.limit = target;
+        metrics().fetch_permits_max.set(target as u64);
+    }
+
+    fn publish(&self) {
+        let limit = self.window.lock().unwrap().limit;
+        let outstanding = limit + self.debt.load(Ordering::Acquire);
+        metrics().fetch_permits_in_use.set(outstanding.saturating_sub(self.available()) as u64);
+        metrics().fetch_permits_max.set(limit as u64);
+    }
+}
+
+pub struct ComputeBudget {
+    per_sec: f64,
+    burst: f64,
+    bucket: tokio::sync::Mutex<(f64, Instant)>,
+}
+
+impl ComputeBudget {
+    pub fn new(per_sec: u32, burst: u32) -> Self {
+        let burst = burst.max(per_sec).max(1) as f64;
+        Self {
+            per_sec: per_sec.max(1) as f64,
+            burst,
+            bucket: tokio::sync::Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let per_sec: u32 = std::env::var("RPC_CU_PER_SEC").ok().and_then(|v| v.parse().ok())?;
+        let burst = std::env::var("RPC_CU_BURST")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(per_sec);
+
+        Some(Self::new(per_sec, burst))
+    }
+
+    pub async fn spend(&self, cost: u32) {
+        let cost = (cost as f64).min(self.burst);
+        let mut bucket = self.bucket.lock().await;
+
+        let (tokens, refilled_at) = &mut *bucket;
+        let now = Instant::now();
+        *tokens = (*tokens + now.duration_since(*refilled_at).as_secs_f64() * self.per_sec).min(self.burst);
+        *refilled_at = now;
+
+        if *tokens < cost {
+            let wait = Duration::from_secs_f64((cost - *tokens) / self.per_sec);
+            metrics().rpc_budget_waits.inc();
+            tokio::time::sleep(wait).await;
+            *tokens = cost;
+            *refilled_at = Instant::now();
+        }
+
+        *tokens -= cost;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(min: usize, max: usize, initial: usize) -> AimdConfig {
+        AimdConfig {
+            min,
+            max,
+            initial,
+            latency_target: Duration::from_millis(100),
+            fetch_timeout: Duration::from_secs(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_additive_increase_on_healthy_window() {
+        let limiter = AdaptiveLimiter::new(config(1, 10, 4));
+
+        for _ in 0..4 {
+            limiter.record(FetchOutcome::Ok(Duration::from_millis(20)));
+        }
+        assert_eq!(limiter.limit(), 5);
+        assert_eq!(limiter.available(), 5);
+
+        for _ in 0..4 {
+            limiter.record(FetchOutcome::Ok(Duration::from_millis(20)));
+        }
+        limiter.record(FetchOutcome::Ok(Duration::from_millis(500)));
+        assert_eq!(limiter.limit(), 5, "a slow fetch holds the limit");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_multiplicative_decrease_with_cooldown() {
+        let limiter = AdaptiveLimiter::new(config(2, 64, 32));
+
+        limiter.record(FetchOutcome::RateLimited);
+        assert_eq!(limiter.limit(), 16);
+        limiter.record(FetchOutcome::Timeout);
+        assert_eq!(limiter.limit(), 16, "one decrease per cooldown");
+
+        tokio::time::sleep(DECREASE_COOLDOWN).await;
+        limiter.record(FetchOutcome::RateLimited);
+        assert_eq!(limiter.limit(), 8);
+
+        for _ in 0..8 {
+            tokio::time::sleep(DECREASE_COOLDOWN).await;
+            limiter.record(FetchOutcome::RateLimited);
+        }
+        assert_eq!(limiter.limit(), 2, "never below the minimum");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_error_ratio_backs_off() {
+        let limiter = AdaptiveLimiter::new(config(1, 64, 8));
+        for i in 0..8 {
+            limiter.record(if i < 2 { FetchOutcome::Error } else { FetchOutcome::Ok(Duration::ZERO) });
+        }
+        assert_eq!(limiter.limit(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_try_acquire_stops_at_limit() {
+        let limiter = AdaptiveLimiter::new(config(1, 8, 2));
+        let first = limiter.try_acquire().unwrap();
+        let _second = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+
+        drop(first);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shrink_waits_for_held_permits() {
+        let limiter = AdaptiveLimiter::new(config(1, 8, 4));
+        let held: Vec<_> = futures_util::future::join_all((0..4).map(|_| limiter.acquire())).await;
+        assert_eq!(limiter.available(), 0);
+
+        limiter.record(FetchOutcome::RateLimited);
+        assert_eq!(limiter.limit(), 2);
+
+        drop(held);
+        assert_eq!(limiter.available(), 2, "two permits were retired on release");
+
+        let started = Instant::now();
+        assert_eq!(limiter.drain(started + Duration::from_secs(1)).await, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_compute_budget_paces_spending() {
+        let budget = ComputeBudget::new(100, 100);
+        let started = Instant::now();
+
+        for _ in 0..5 {
+            budget.spend(20).await;
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO, "burst covers the first 100 cu");
+
+        budget.spend(50).await;
+        assert_eq!(started.elapsed(), Duration::from_millis(500));
+
+        budget.spend(100).await;
+        assert_eq!(started.elapsed(), Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,399 @@
+use crate::action::{SniperAction, TargetTransaction};
+use crate::alert_queue::{AlertQueue, DropPolicy, PushOutcome};
+use crate::format::{DetectionPayload, SCHEMA_VERSION};
+use crate::metrics::metrics;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::StatusCode;
+use sha2::Sha256;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const SIGNATURE_HEADER: &str = "x-sniper-signature";
+pub const SCHEMA_HEADER: &str = "x-sniper-schema-version";
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    pub secret: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub max_attempts: u32,
+    pub timeout: Duration,
+    pub queue_capacity: usize,
+    pub chain_id: Option<u64>,
+}
+
+impl WebhookConfig {
+    pub fn from_env(chain_id: Option<u64>) -> Option<Self> {
+        let urls: Vec<String> = std::env::var("WEBHOOK_URLS")
+            .ok()?
+            .split(',')
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .collect();
+
+        if urls.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            urls,
+            secret: std::env::var("WEBHOOK_SECRET").ok().filter(|s| !s.is_empty()),
+            headers: std::env::var("WEBHOOK_HEADERS")
+                .map(|v| parse_headers(&v))
+                .unwrap_or_default(),
+            max_attempts: std::env::var("WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            timeout: std::env::var("WEBHOOK_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_TIMEOUT),
+            queue_capacity: std::env::var("WEBHOOK_QUEUE_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(crate::alert_queue::DEFAULT_CAPACITY),
+            chain_id,
+        })
+    }
+}
+
+pub fn parse_headers(spec: &str) -> Vec<(String, String)> {
+    spec.split(';')
+        .filter_map(|pair| pair.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+struct Endpoint {
+    url: String,
+    queue: AlertQueue,
+}
+
+#[derive(Clone)]
+pub struct WebhookSink {
+    client: reqwest::Client,
+    config: Arc<WebhookConfig>,
+    headers: HeaderMap,
+    endpoints: Vec<Arc<Endpoint>>,
+    started: Arc<AtomicBool>,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(SCHEMA_HEADER, HeaderValue::from(SCHEMA_VERSION));
+        for (name, value) in &config.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).with_context(|| format!("invalid webhook header name {:?}", name))?,
+                HeaderValue::from_str(value).with_context(|| format!("invalid value for webhook header {:?}", name))?,
+            );
+        }
+
+        tracing::info!(
+            "webhook sink initialized ({} endpoints, signed: {})",
+            config.urls.len(),
+            config.secret.is_some()
+        );
+
+        let endpoints = config
+            .urls
+            .iter()
+            .enumerate()
+            .map(|(i, url)| {
+                Arc::new(Endpoint {
+                    url: url.clone(),
+                    queue: AlertQueue::new(config.queue_capacity, DropPolicy::DropLowestValue, None)
+                        .with_depth(metrics().alert_queue_depth.with_label(&format!("webhook:{}", i))),
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(config.timeout).build()?,
+            config: Arc::new(config),
+            headers,
+            endpoints,
+            started: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    async fn run_endpoint(self, endpoint: Arc<Endpoint>) {
+        loop {
+            let tx = endpoint.queue.pop().await;
+
+            match self.deliver(&endpoint.url, &tx).await {
+                Ok(()) => metrics().alerts_delivered.inc(),
+                Err(e) => {
+                    metrics().webhook_send_failures.inc();
+                    tracing::error!("failed to deliver webhook for tx {:?} to {}: {}", tx.tx_hash, endpoint.url, e);
+                }
+            }
+
+            endpoint.queue.done();
+        }
+    }
+
+    async fn deliver(&self, url: &str, tx: &TargetTransaction) -> Result<()> {
+        let body = serde_json::to_vec(&DetectionPayload::new(tx, self.config.chain_id))?;
+
+        let mut headers = self.headers.clone();
+        if let Some(secret) = &self.config.secret {
+            headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(&sign(secret, &body))?);
+        }
+
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            let result = self
+                .client
+                .post(url)
+                .headers(headers.clone())
+                .body(body.clone())
+                .send()
+                .await;
+
+            let (error, retry_after) = match result {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = response
+                        .headers()
+                        .get("retry-after")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    let body = response.text().await.unwrap_or_default();
+                    let error = anyhow::anyhow!("webhook returned error {}: {}", status, body);
+
+                    if !retryable(status) {
+                        return Err(error);
+                    }
+                    (error, retry_after)
+                }
+                Err(e) => (anyhow::Error::new(e).context("failed to send webhook"), None),
+            };
+
+            if attempt >= self.config.max_attempts {
+                return Err(error);
+            }
+
+            let backoff = retry_after.unwrap_or_else(|| {
+                INITIAL_BACKOFF
+                    .saturating_mul(2u32.saturating_pow(attempt - 1))
+                    .min(MAX_BACKOFF)
+            });
+            metrics().alerts_retried.inc();
+            tracing::warn!(
+                "webhook delivery to {} failed: {} - retrying in {:?} (attempt {}/{})",
+                url,
+                error,
+                backoff,
+                attempt,
+                self.config.max_attempts
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
+
+fn retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT || status.is_server_error()
+}
+
+#[async_trait]
+impl SniperAction for WebhookSink {
+    async fn start(&self) -> Result<()> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        for endpoint in &self.endpoints {
+            tokio::spawn(self.clone().run_endpoint(endpoint.clone()));
+        }
+
+        Ok(())
+    }
+
+    async fn execute(&self, tx: &TargetTransaction) -> Result<()> {
+        for endpoint in &self.endpoints {
+            if endpoint.queue.push(tx.clone()).await == PushOutcome::Dropped {
+                tracing::debug!("webhook queue for {} full - dropped alert for tx {:?}", endpoint.url, tx.tx_hash);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&self, timeout: Duration) -> Result<()> {
+        let idle = join_all(self.endpoints.iter().map(|endpoint| endpoint.queue.wait_idle(timeout))).await;
+        let pending: usize = self
+            .endpoints
+            .iter()
+            .zip(idle)
+            .filter(|(_, idle)| !idle)
+            .map(|(endpoint, _)| endpoint.queue.depth())
+            .sum();
+
+        if pending > 0 {
+            anyhow::bail!("{} webhook deliveries still queued after {:?}", pending, timeout);
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::mock_http::{response, MockHttp};
+    use alloy::primitives::U256;
+
+    fn config(url: &str) -> WebhookConfig {
+        WebhookConfig {
+            urls: vec![url.to_string()],
+            secret: Some("s3cret".to_string()),
+            headers: vec![("Authorization".to_string(), "Bearer token".to_string())],
+            max_attempts: 3,
+            timeout: Duration::from_secs(5),
+            queue_capacity: 100,
+            chain_id: Some(11_155_111),
+        }
+    }
+
+    fn sample_tx() -> TargetTransaction {
+        TargetTransaction {
+            amount_out_min: U256::from(42u64),
+            deadline: U256::from(1_700_000_000u64),
+            detected_at: 1_700_000_000_123,
+            ..fixtures::sample_tx()
+        }
+    }
+
+    #[test]
+    fn test_payload_schema() {
+        let payload = serde_json::to_value(DetectionPayload::new(&sample_tx(), Some(1))).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "schema_version": 1,
+                "event": "transaction_detected",
+                "chain_id": 1,
+                "tx_hash": format!("0x{}", "11".repeat(32)),
+                "from": format!("0x{}", "22".repeat(20)),
+                "to": format!("0x{}", "44".repeat(20)),
+                "value_wei": "1000000000000000000",
+                "method": "swapExactETHForTokens",
+                "path": [format!("0x{}", "33".repeat(20))],
+                "amount_out_min": "42",
+                "deadline": "1700000000",
+                "detected_at": 1_700_000_000_123u64
+            })
+        );
+
+        let mined = TargetTransaction {
+            block_number: Some(19_000_000),
+            ..sample_tx()
+        };
+        let payload = serde_json::to_value(DetectionPayload::new(&mined, Some(1))).unwrap();
+        assert_eq!(payload["event"], "transaction_mined");
+        assert_eq!(payload["block_number"], 19_000_000);
+    }
+
+    #[test]
+    fn test_signature() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        assert_eq!(
+            parse_headers("Authorization: Bearer abc; X-Team:mev ;invalid"),
+            vec![
+                ("Authorization".to_string(), "Bearer abc".to_string()),
+                ("X-Team".to_string(), "mev".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_delivery_with_retry() {
+        let server = MockHttp::start(vec![
+            response("503 Service Unavailable", "Retry-After: 0\r\n", ""),
+            response("200 OK", "", "{}"),
+        ])
+        .await;
+
+        let sink = WebhookSink::new(config(&format!("{}/hooks/sniper", server.url))).unwrap();
+        sink.start().await.unwrap();
+        sink.execute(&sample_tx()).await.unwrap();
+        sink.flush(Duration::from_secs(5)).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+
+        let request = &requests[1];
+        assert_eq!(request.path, "/hooks/sniper");
+        assert_eq!(request.header("authorization"), Some("Bearer token"));
+        assert_eq!(request.header(SCHEMA_HEADER), Some("1"));
+        assert_eq!(request.header(SIGNATURE_HEADER), Some(sign("s3cret", request.body.as_bytes()).as_str()));
+        assert_eq!(request.json()["tx_hash"], format!("0x{}", "11".repeat(32)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flush_shares_one_timeout_across_endpoints() {
+        let sink = WebhookSink::new(WebhookConfig {
+            urls: vec!["http://127.0.0.1:9/a".to_string(), "http://127.0.0.1:9/b".to_string()],
+            ..config("http://127.0.0.1:9/unused")
+        })
+        .unwrap();
+        sink.execute(&sample_tx()).await.unwrap();
+
+        let started = tokio::time::Instant::now();
+        let err = sink.flush(Duration::from_millis(100)).await.unwrap_err();
+
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+        assert!(err.to_string().starts_with("2 webhook deliveries"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_client_error_not_retried() {
+        let server = MockHttp::start(vec![response("401 Unauthorized", "", "")]).await;
+
+        let sink = WebhookSink::new(config(&server.url)).unwrap();
+        sink.start().await.unwrap();
+        let result = sink.deliver(&server.url, &sample_tx()).await;
+
+        assert!(result.is_err());
+        assert_eq!(server.hits(), 1);
+    }
+}